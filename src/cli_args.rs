@@ -1,3 +1,4 @@
+use crate::config::{find_file, Handout, ImageCompression, ImageOptions};
 use std::path::PathBuf;
 use structopt::StructOpt;
 
@@ -13,6 +14,29 @@ struct Opts {
     present_file: PathBuf,
     #[structopt(short = "n", default_value = "presentation")]
     docname: String,
+    /// images with a higher resolution are scaled down to it
+    #[structopt(long)]
+    image_dpi: Option<u16>,
+    /// the compression of images: flate (lossless) or jpeg
+    #[structopt(long)]
+    image_compression: Option<ImageCompression>,
+    /// the quality of jpeg compressed images from 1 to 100
+    #[structopt(long)]
+    image_quality: Option<u8>,
+    /// creates a handout for printing with 2, 4 or 6 slides on every a4 page
+    #[structopt(long, possible_values = &["2", "4", "6"])]
+    handout: Option<u8>,
+    /// draws lines for notes next to the slides of the handout
+    #[structopt(long, requires = "handout")]
+    notes: bool,
+    /// the name of a theme inside the themes directory of the config,
+    /// it bundles a style, templates, fonts and images
+    #[structopt(long)]
+    theme: Option<String>,
+    /// directories where images and styles are searched,
+    /// if they aren't next to the file using them
+    #[structopt(short = "p", long)]
+    search_path: Vec<PathBuf>,
 }
 
 pub struct CliArgs {
@@ -21,6 +45,13 @@ pub struct CliArgs {
     pub output: PathBuf,
     pub present_file: PathBuf,
     pub doc_name: String,
+    /// overrides the image settings of the style
+    pub image: ImageOptions,
+    pub handout: Option<Handout>,
+    /// directories with fonts besides the system fonts
+    pub font_dirs: Vec<PathBuf>,
+    /// directories where images and styles are searched
+    pub search_paths: Vec<PathBuf>,
 }
 
 fn get_project_dir() -> directories::ProjectDirs {
@@ -28,17 +59,55 @@ fn get_project_dir() -> directories::ProjectDirs {
         .expect("Unknown operating system, couldn't find a good project directory")
 }
 
+/// the directory of the theme with the name
+/// inside the themes directory of the config
+fn get_theme(dir: &directories::ProjectDirs, name: &str) -> PathBuf {
+    let theme = dir.config_dir().join("themes").join(name);
+
+    if !theme.is_dir() {
+        eprintln!("the theme {} doesn't exist at {}", name, theme.to_string_lossy());
+        std::process::exit(1);
+    }
+
+    theme
+}
+
 pub fn get() -> CliArgs {
     let mut opts = Opts::from_args();
     let dir = get_project_dir();
-    
-    opts.templates.push(dir.config_dir().join("template.hjson"));
-    
+    let theme = opts.theme.as_ref().map(|name| get_theme(&dir, name));
+
+    // a theme replaces the templates of the config directory
+    // and its templates can be overridden by the given ones
+    match &theme {
+        Some(theme) => opts.templates.insert(0, find_file(theme, "template")),
+        None => opts.templates.push(find_file(dir.config_dir(), "template")),
+    }
+
+    // the images of the theme can be used by the slides too
+    opts.search_path.extend(theme.clone());
+
+    let default_style = match &theme {
+        Some(theme) => find_file(theme, "style"),
+        None => find_file(dir.config_dir(), "style"),
+    };
+
     CliArgs {
+        font_dirs: theme.iter().map(|t| t.join("fonts")).collect(),
+        handout: opts.handout.map(|slides_per_page| Handout {
+            slides_per_page,
+            notes: opts.notes,
+        }),
         doc_name: opts.docname,
+        image: ImageOptions {
+            max_dpi: opts.image_dpi,
+            compression: opts.image_compression,
+            quality: opts.image_quality,
+        },
         output: opts.output,
         present_file: opts.present_file,
         templates: opts.templates,
-        style: opts.style.unwrap_or_else(|| dir.config_dir().join("style.hjson")),
+        search_paths: opts.search_path,
+        style: opts.style.unwrap_or(default_style),
     }
 }
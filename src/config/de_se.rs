@@ -1,14 +1,53 @@
-use super::{Point, Rectangle};
+use super::{error::Checker, ColorRef, Point, Rectangle};
+use crate::util::pdf::Size;
 use serde_derive::Deserialize;
 use std::collections::HashMap;
 
 #[derive(Debug, Deserialize)]
 pub struct StyleJson {
     pub colors: Vec<String>,
+    #[serde(default, rename = "namedColors")]
+    pub named_colors: Option<HashMap<String, ColorJson>>,
     pub font: String,
     pub margin: Rectangle<f64>,
     #[serde(rename = "lineSpace")]
     pub line_spacing: f64,
+    #[serde(default)]
+    pub image: Option<ImageJson>,
+    #[serde(default, rename = "pageSize")]
+    pub page_size: Option<String>,
+    #[serde(default)]
+    pub link: Option<LinkJson>,
+    #[serde(default)]
+    pub furniture: Option<Vec<FurnitureJson>>,
+    #[serde(default, rename = "dimColor")]
+    pub dim_color: Option<ColorJson>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FurnitureJson {
+    pub orig: Point<f64>,
+    pub size: Point<f64>,
+    pub orientation: String,
+    #[serde(rename = "fontSize")]
+    pub font_size: f32,
+    #[serde(default)]
+    pub color: Option<ColorJson>,
+    pub text: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LinkJson {
+    pub color: Option<ColorJson>,
+    pub underline: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImageJson {
+    #[serde(rename = "maxDpi")]
+    pub max_dpi: Option<u16>,
+    pub compression: Option<String>,
+    pub quality: Option<u8>,
 }
 
 /*#[derive(Debug, Deserialize)]
@@ -19,115 +58,573 @@ pub type TemplateJson = HashMap<String, SlideTemplate>;
 
 #[derive(Debug, Deserialize)]
 pub struct SlideTemplate {
+    /// the name of the template it inherits from
+    #[serde(default)]
+    pub extends: Option<String>,
+    #[serde(default)]
     pub decoration: Vec<DecorationJson>,
+    #[serde(default)]
+    pub background: Option<ColorJson>,
+    #[serde(default)]
     pub template: Vec<ContentTemplate>,
+    #[serde(default)]
+    pub furniture: Option<bool>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct ContentTemplate {
+    #[serde(default)]
+    pub name: Option<String>,
     pub orig: Point<f64>,
     pub size: Point<f64>,
     pub orientation: String,
     #[serde(rename = "fontSize")]
     pub font_size: f32,
+    #[serde(default)]
+    pub caption: Option<CaptionJson>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CaptionJson {
+    pub position: String,
+    pub size: f64,
+    #[serde(rename = "fontSize")]
+    pub font_size: f32,
+    pub orientation: String,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct DecorationJson {
     pub orig: Point<f64>,
     pub size: Point<f64>,
-    pub color: usize,
-}
-
-impl From<StyleJson> for super::PresentStyle {
-    fn from(json: StyleJson) -> Self {
-        Self {
-            font: json.font,
-            line_spacing: json.line_spacing,
-            margin: json.margin,
-            colors: json
-                .colors
-                .iter()
-                // skip the # at the beginning
-                .map(|hex| hex_string_to_color(&hex[1..]))
-                .collect(),
+    #[serde(default)]
+    pub shape: Option<String>,
+    /// the radius of rounded rectangles
+    #[serde(default)]
+    pub radius: Option<f64>,
+    /// the corners of polygons
+    #[serde(default)]
+    pub points: Option<Vec<Point<f64>>>,
+    /// the fill color
+    #[serde(default)]
+    pub color: Option<ColorJson>,
+    /// fills the shape instead of the color
+    #[serde(default)]
+    pub gradient: Option<GradientJson>,
+    #[serde(default)]
+    pub stroke: Option<StrokeJson>,
+    /// the path of an image drawn into the area
+    #[serde(default)]
+    pub image: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GradientJson {
+    /// linear or radial
+    #[serde(default)]
+    pub kind: Option<String>,
+    pub from: ColorJson,
+    pub to: ColorJson,
+    #[serde(default)]
+    pub angle: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StrokeJson {
+    pub color: ColorJson,
+    #[serde(default)]
+    pub width: Option<f64>,
+    #[serde(default)]
+    pub dash: Option<Vec<i64>>,
+}
+
+/// a color before it's checked, either an index or a name or color in hex
+#[derive(Debug)]
+pub enum ColorJson {
+    Index(usize),
+    Text(String),
+}
+
+/// converts the parsed json into the config, the
+/// problems with the values are added to the checker
+pub trait Convert {
+    type Output;
+
+    fn convert(self, c: &mut Checker) -> Self::Output;
+}
+
+impl Convert for StyleJson {
+    type Output = super::PresentStyle;
+
+    fn convert(self, c: &mut Checker) -> Self::Output {
+        let colors = c.list("colors", self.colors, |c, color| {
+            // the hex needs a prefix, because # is a comment in hjson
+            match color.strip_prefix('%').and_then(parse_hex) {
+                Some(color) => color,
+                None => {
+                    c.invalid("", color, HEX_ERR);
+                    super::Color::new(0.0, 0.0, 0.0)
+                }
+            }
+        });
+        let named_colors = self.named_colors.unwrap_or_default();
+        let named_colors = c.key("namedColors", |c| {
+            named_colors
+                .into_iter()
+                .map(|(name, color)| {
+                    let color = c.key(&name, |c| color.convert(c));
+                    (name, color)
+                })
+                .collect()
+        });
+
+        Self::Output {
+            font: self.font,
+            line_spacing: self.line_spacing,
+            margin: self.margin,
+            image: self
+                .image
+                .map(|i| {
+                    let mut image = super::ImageStyle::default();
+                    c.key("image", |c| i.convert(c)).apply(&mut image);
+                    image
+                })
+                .unwrap_or_default(),
+            page_size: self
+                .page_size
+                .and_then(|s| {
+                    let size = str_to_page_size(&s);
+                    if size.is_none() {
+                        c.invalid("pageSize", s, PAGE_SIZE_ERR);
+                    }
+                    size
+                })
+                .unwrap_or_else(|| super::PresentStyle::default().page_size),
+            link: self
+                .link
+                .map(|l| c.key("link", |c| l.convert(c)))
+                .unwrap_or_default(),
+            furniture: c.list("furniture", self.furniture.unwrap_or_default(), |c, f| {
+                f.convert(c)
+            }),
+            dim_color: self
+                .dim_color
+                .map(|color| c.key("dimColor", |c| color.convert(c))),
+            named_colors,
+            colors,
+        }
+    }
+}
+
+impl Convert for ColorJson {
+    type Output = ColorRef;
+
+    fn convert(self, c: &mut Checker) -> Self::Output {
+        let text = match self {
+            Self::Index(idx) => return ColorRef::Index(idx),
+            Self::Text(text) => text,
+        };
+
+        match text.strip_prefix('%').or_else(|| text.strip_prefix('#')) {
+            Some(hex) => match parse_hex(hex) {
+                Some(color) => ColorRef::Color(color),
+                None => {
+                    c.invalid("", &text, HEX_ERR);
+                    ColorRef::Color(super::Color::new(0.0, 0.0, 0.0))
+                }
+            },
+            None => ColorRef::Name(text),
+        }
+    }
+}
+
+impl Convert for ImageJson {
+    type Output = super::ImageOptions;
+
+    fn convert(self, c: &mut Checker) -> Self::Output {
+        Self::Output {
+            max_dpi: self.max_dpi,
+            compression: self.compression.and_then(|s| match s.parse() {
+                Ok(compression) => Some(compression),
+                Err(_) => {
+                    c.invalid("compression", s, "flate or jpeg");
+                    None
+                }
+            }),
+            quality: self.quality,
+        }
+    }
+}
+
+impl Convert for LinkJson {
+    type Output = super::LinkStyle;
+
+    fn convert(self, c: &mut Checker) -> Self::Output {
+        let default = Self::Output::default();
+        Self::Output {
+            color: self
+                .color
+                .map(|color| c.key("color", |c| color.convert(c)))
+                .or(default.color),
+            underline: self.underline.unwrap_or(default.underline),
+        }
+    }
+}
+
+impl Convert for FurnitureJson {
+    type Output = super::Furniture;
+
+    fn convert(self, c: &mut Checker) -> Self::Output {
+        Self::Output {
+            area: super::Rectangle {
+                orig: self.orig,
+                size: self.size,
+            },
+            font_size: self.font_size,
+            orientation: convert_orientation(c, "orientation", &self.orientation),
+            color: self.color.map(|color| c.key("color", |c| color.convert(c))),
+            text: self.text,
+        }
+    }
+}
+
+impl Convert for SlideTemplate {
+    type Output = super::SlideTemplate;
+
+    fn convert(self, c: &mut Checker) -> Self::Output {
+        Self::Output {
+            decorations: c.list("decoration", self.decoration, |c, d| d.convert(c)),
+            background: self
+                .background
+                .map(|color| c.key("background", |c| color.convert(c))),
+            content: c.list("template", self.template, |c, t| t.convert(c)),
+            furniture: self.furniture.unwrap_or(true),
         }
     }
 }
 
-impl From<SlideTemplate> for super::SlideTemplate {
-    fn from(json: SlideTemplate) -> Self {
-        Self {
-            decorations: json.decoration.into_iter().map(|d| d.into()).collect(),
-            content: json.template.into_iter().map(|t| t.into()).collect(),
+impl Convert for DecorationJson {
+    type Output = super::Decoration;
+
+    fn convert(self, c: &mut Checker) -> Self::Output {
+        use super::Shape;
+        let shape = match self.shape.as_deref().map(str::to_lowercase).as_deref() {
+            None | Some("rectangle") => Shape::Rectangle,
+            Some("rounded") => Shape::RoundedRectangle(self.radius.unwrap_or(0.1)),
+            Some("ellipse") => Shape::Ellipse,
+            Some("line") => Shape::Line,
+            Some("polygon") => match self.points {
+                Some(points) => Shape::Polygon(points),
+                None => {
+                    c.invalid("points", "nothing", "the corners of the polygon");
+                    Shape::Rectangle
+                }
+            },
+            Some(_) => {
+                c.invalid("shape", self.shape.unwrap_or_default(), SHAPE_ERR);
+                Shape::Rectangle
+            }
+        };
+        let fill = match self.gradient {
+            Some(g) => Some(c.key("gradient", |c| g.convert(c))),
+            None => self
+                .color
+                .map(|color| super::Fill::Color(c.key("color", |c| color.convert(c)))),
+        };
+
+        Self::Output {
+            area: super::Rectangle {
+                orig: self.orig,
+                size: self.size,
+            },
+            shape,
+            fill,
+            stroke: self.stroke.map(|s| super::Stroke {
+                color: {
+                    let color = s.color;
+                    c.key("stroke", |c| c.key("color", |c| color.convert(c)))
+                },
+                width: s.width.unwrap_or(1.0),
+                dash: s.dash.unwrap_or_default(),
+            }),
+            image: self.image.map(|i| i.into()),
         }
     }
 }
 
-impl From<DecorationJson> for super::Decoration {
-    fn from(json: DecorationJson) -> Self {
-        let DecorationJson { orig, size, color } = json;
-        Self {
-            area: super::Rectangle { orig, size },
-            color_idx: color,
+impl Convert for GradientJson {
+    type Output = super::Fill;
+
+    fn convert(self, c: &mut Checker) -> Self::Output {
+        let (from, to) = (self.from, self.to);
+        let from = c.key("from", |c| from.convert(c));
+        let to = c.key("to", |c| to.convert(c));
+
+        match self.kind.as_deref().map(str::to_lowercase).as_deref() {
+            Some("radial") => Self::Output::Radial { from, to },
+            kind => {
+                if kind.is_some() && kind != Some("linear") {
+                    c.invalid("kind", self.kind.unwrap_or_default(), "linear or radial");
+                }
+
+                Self::Output::Linear {
+                    from,
+                    to,
+                    angle: self.angle.unwrap_or(0.0),
+                }
+            }
         }
     }
 }
 
-impl From<ContentTemplate> for super::ContentTemplate {
-    fn from(json: ContentTemplate) -> Self {
-        Self {
+const SHAPE_ERR: &str = "rectangle, rounded, ellipse, line or polygon";
+
+impl Convert for ContentTemplate {
+    type Output = super::ContentTemplate;
+
+    fn convert(self, c: &mut Checker) -> Self::Output {
+        Self::Output {
+            name: self.name,
             area: super::Rectangle {
-                orig: json.orig,
-                size: json.size,
+                orig: self.orig,
+                size: self.size,
             },
-            font_size: json.font_size,
-            orientation: str_to_orientation(&json.orientation),
+            font_size: self.font_size,
+            orientation: convert_orientation(c, "orientation", &self.orientation),
+            caption: self
+                .caption
+                .map(|caption| c.key("caption", |c| caption.convert(c))),
+        }
+    }
+}
+
+impl Convert for CaptionJson {
+    type Output = super::CaptionTemplate;
+
+    fn convert(self, c: &mut Checker) -> Self::Output {
+        use super::CaptionPosition;
+        let position = match self.position.to_lowercase().as_str() {
+            "above" => CaptionPosition::Above,
+            "below" => CaptionPosition::Below,
+            _ => {
+                c.invalid("position", &self.position, "above or below");
+                CaptionPosition::Below
+            }
+        };
+
+        Self::Output {
+            position,
+            size: self.size,
+            font_size: self.font_size,
+            orientation: convert_orientation(c, "orientation", &self.orientation),
         }
     }
 }
 
-const ORIENT_ERR: &str = "orientation not in the right format";
+const ORIENT_ERR: &str = "\"<top|middle|bottom> <left|middle|right>\"";
+
+/// converts the orientation, if it's invalid the default is used
+pub fn convert_orientation(c: &mut Checker, key: &str, s: &str) -> super::Orientation {
+    str_to_orientation(s).unwrap_or_else(|| {
+        c.invalid(key, s, ORIENT_ERR);
+        super::Orientation::default()
+    })
+}
 
 /// converts a string in the format of "<vert> <hor>"
 /// ex. "top left" into an orientation
-fn str_to_orientation(s: &str) -> super::Orientation {
+fn str_to_orientation(s: &str) -> Option<super::Orientation> {
     use super::{HorOrientation, VertOrientation};
     let lower = s.to_lowercase();
     let mut words = lower.split_whitespace();
 
-    let vert = match words.next().expect(ORIENT_ERR) {
+    let vert = match words.next()? {
         "bottom" => VertOrientation::Bottom,
         "middle" => VertOrientation::Middle,
         "top" => VertOrientation::Top,
-        _ => panic!("{}", ORIENT_ERR),
+        _ => return None,
     };
 
-    let hort = match words.next().expect(ORIENT_ERR) {
+    let hort = match words.next()? {
         "left" => HorOrientation::Left,
         "middle" => HorOrientation::Middle,
         "right" => HorOrientation::Right,
-        _ => panic!("{}", ORIENT_ERR),
+        _ => return None,
     };
 
     // nothing else behind it
-    assert_eq!(words.next(), None, "{}", ORIENT_ERR);
+    if words.next().is_some() {
+        return None;
+    }
 
-    super::Orientation {
+    Some(super::Orientation {
         vertical: vert,
         horizontal: hort,
-    }
+    })
+}
+
+const PAGE_SIZE_ERR: &str =
+    "a preset or \"<width>x<height> <px|mm|pt>\" with positive sizes, whole numbers for px";
+
+/// converts a preset (16:9, 16:10, 4:3, a4 landscape) or a string in the format
+/// of "<width>x<height> <unit>" ex. "297x210 mm" into a page size.
+/// The screen presets all have the same height, so font sizes look the same.
+/// Sizes which aren't positive would give a broken page, so they're invalid
+fn str_to_page_size(s: &str) -> Option<Size> {
+    let lower = s.to_lowercase();
+    let words = lower.split_whitespace().collect::<Vec<_>>();
+
+    let size = match words.as_slice() {
+        ["16:9"] => Size::Px(1920, 1080),
+        ["16:10"] => Size::Px(1728, 1080),
+        ["4:3"] => Size::Px(1440, 1080),
+        ["a4", "landscape"] => Size::Mm(297.0, 210.0),
+        [size, unit] => {
+            let (x, y) = size.split_once('x')?;
+            let positive = |v: &str| v.parse().ok().filter(|v: &f64| v.is_finite() && *v > 0.0);
+
+            match *unit {
+                "px" => match (x.parse(), y.parse()) {
+                    (Ok(x), Ok(y)) if x > 0 && y > 0 => Size::Px(x, y),
+                    _ => return None,
+                },
+                "mm" => Size::Mm(positive(x)?, positive(y)?),
+                "pt" => Size::Pt(positive(x)?, positive(y)?),
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+
+    Some(size)
 }
 
-fn hex_string_to_color(hex: &str) -> super::Color {
-    let to_color = |c| c as f64 / 256.0;
-    let bytes = u32::from_str_radix(hex, 16)
-        .expect("wrong color format expected RRGGBB in hex")
-        .to_le_bytes();
+const HEX_ERR: &str = "a color like %RRGGBB or %RRGGBBAA in hex";
+
+/// parses the color from RRGGBB or RRGGBBAA in hex
+fn parse_hex(hex: &str) -> Option<super::Color> {
+    let to_color = |c| c as f64 / 255.0;
+    // from_str_radix would take a sign too
+    if !(hex.len() == 6 || hex.len() == 8) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    // colors without alpha are opaque
+    let bytes = if hex.len() == 6 {
+        (value << 8 | 0xFF).to_be_bytes()
+    } else {
+        value.to_be_bytes()
+    };
 
-    super::Color {
-        b: to_color(bytes[0]),
+    Some(super::Color {
+        r: to_color(bytes[0]),
         g: to_color(bytes[1]),
-        r: to_color(bytes[2]),
+        b: to_color(bytes[2]),
+        a: to_color(bytes[3]),
+    })
+}
+
+/// colors are either an index into the colors of the style,
+/// the name of a named color or a color in hex like %RRGGBB
+impl serde::Deserialize for ColorJson {
+    fn deserialize<D: serde::Deserializer>(deserializer: &mut D) -> Result<Self, D::Error> {
+        struct ColorVisitor;
+
+        impl serde::de::Visitor for ColorVisitor {
+            type Value = ColorJson;
+
+            fn visit_u64<E: serde::de::Error>(&mut self, v: u64) -> Result<ColorJson, E> {
+                Ok(ColorJson::Index(v as usize))
+            }
+
+            fn visit_str<E: serde::de::Error>(&mut self, v: &str) -> Result<ColorJson, E> {
+                Ok(ColorJson::Text(String::from(v)))
+            }
+        }
+
+        deserializer.deserialize(ColorVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{HorOrientation, Orientation, VertOrientation};
+    use crate::util::pdf::Size;
+
+    #[test]
+    fn page_size_presets() {
+        assert_eq!(super::str_to_page_size("16:9"), Some(Size::Px(1920, 1080)));
+        assert_eq!(super::str_to_page_size("4:3"), Some(Size::Px(1440, 1080)));
+        assert_eq!(
+            super::str_to_page_size("A4  Landscape"),
+            Some(Size::Mm(297.0, 210.0))
+        );
+    }
+
+    #[test]
+    fn page_size_units() {
+        assert_eq!(
+            super::str_to_page_size("800x600 px"),
+            Some(Size::Px(800, 600))
+        );
+        assert_eq!(
+            super::str_to_page_size("297x210 mm"),
+            Some(Size::Mm(297.0, 210.0))
+        );
+        assert_eq!(
+            super::str_to_page_size("720.5x405 pt"),
+            Some(Size::Pt(720.5, 405.0))
+        );
+    }
+
+    #[test]
+    fn page_size_invalid() {
+        for size in &[
+            "-800x600 px",
+            "800.5x600 px",
+            "0x600 px",
+            "297x0 mm",
+            "297x-210 mm",
+            "infx100 pt",
+            "297x210",
+            "297x210 in",
+            "297 mm",
+            "a4",
+        ] {
+            assert_eq!(super::str_to_page_size(size), None, "{}", size);
+        }
+    }
+
+    #[test]
+    fn orientation() {
+        assert_eq!(
+            super::str_to_orientation("Bottom  middle"),
+            Some(Orientation {
+                vertical: VertOrientation::Bottom,
+                horizontal: HorOrientation::Middle,
+            })
+        );
+        assert_eq!(super::str_to_orientation("left top"), None);
+        assert_eq!(super::str_to_orientation("top"), None);
+        assert_eq!(super::str_to_orientation("top left right"), None);
+    }
+
+    #[test]
+    fn hex_colors() {
+        let color = super::parse_hex("FF0080").unwrap();
+        assert_eq!(
+            (color.r, color.g, color.b, color.a),
+            (1.0, 0.0, 128.0 / 255.0, 1.0)
+        );
+
+        let color = super::parse_hex("00ff0000").unwrap();
+        assert_eq!((color.r, color.g, color.b, color.a), (0.0, 1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn hex_colors_invalid() {
+        for hex in &["", "FFF", "FF00FF0", "FF00FF000", "GG0000", "+FFFFF"] {
+            assert!(super::parse_hex(hex).is_none(), "{}", hex);
+        }
     }
 }
@@ -6,6 +6,9 @@ impl<'a> Default for super::Config<'a> {
             style: Default::default(),
             slide_templates: default_slide_templates(),
             doc_name: "presentation",
+            handout: None,
+            font_dirs: vec![],
+            search_paths: vec![],
         }
     }
 }
@@ -15,104 +18,616 @@ pub fn default_slide_templates() -> TemplateMap {
         vertical: VertOrientation::Bottom,
         horizontal: HorOrientation::Middle,
     };
+    let title_orientation = Orientation {
+        vertical: VertOrientation::Bottom,
+        horizontal: HorOrientation::Left,
+    };
+    let caption_orientation = Orientation {
+        vertical: VertOrientation::Top,
+        horizontal: HorOrientation::Middle,
+    };
+    let centered = Orientation {
+        vertical: VertOrientation::Middle,
+        horizontal: HorOrientation::Middle,
+    };
+    let quote_orientation = Orientation {
+        vertical: VertOrientation::Middle,
+        horizontal: HorOrientation::Left,
+    };
+    let author_orientation = Orientation {
+        vertical: VertOrientation::Top,
+        horizontal: HorOrientation::Right,
+    };
+    // the decorations are lines in the foreground color,
+    // every style has it so they work with all of them
     crate::map! {
         "Title" => SlideTemplate {
             decorations: vec![],
+            background: None,
+            // the title slide is left without slide numbers and footers
+            furniture: false,
             content: vec![
                 ContentTemplate {
+                    name: Some(String::from("title")),
                     area: Rectangle {
                         orig: Point{x: 0.0,y: 0.0},
                         size: Point{x: 1.0,y: 0.8} },
                     font_size: 36.0,
                     orientation: header_orientation.clone(),
+                    caption: None,
                 },
                 ContentTemplate {
+                    name: Some(String::from("subtitle")),
                     area: Rectangle {
                         orig: Point{x: 0.0,y: 0.8},
                         size: Point{x: 1.0,y: 0.2} },
                     font_size: 18.0,
                     orientation: Orientation::default(),
+                    caption: None,
                 },
             ],
         },
 
         "Head_Cont" => SlideTemplate {
             decorations: vec![],
+            background: None,
+            furniture: true,
             content: vec![
                 ContentTemplate {
+                    name: Some(String::from("title")),
                     area: Rectangle {
                         orig: Point{x: 0.0,y: 0.0},
                         size: Point{x: 1.0,y: 0.3},
                     },
                     font_size: 24.0,
                     orientation: header_orientation.clone(),
+                    caption: None,
                 },
                 ContentTemplate {
+                    name: Some(String::from("body")),
                     area: Rectangle {
                         orig: Point{x: 0.0,y: 0.3},
                         size: Point{x: 1.0,y: 0.7},
                     },
                     font_size: 18.0,
                     orientation: Orientation::default(),
+                    caption: None,
                 },
             ],
         },
 
         "Vert_Split" => SlideTemplate {
             decorations: vec![],
+            background: None,
+            furniture: true,
             content: vec![
                 ContentTemplate {
+                    name: Some(String::from("left_title")),
                     area: Rectangle {
                         orig: Point{x: 0.0,y: 0.0},
                         size: Point{x: 0.5,y: 0.3},
                     },
                     font_size: 24.0,
                     orientation: header_orientation.clone(),
+                    caption: None,
                 },
                 ContentTemplate {
+                    name: Some(String::from("left")),
                     area: Rectangle {
                         orig: Point{x: 0.0,y: 0.3},
                         size: Point{x: 0.5,y: 0.7},
                     },
                     font_size: 18.0,
                     orientation: Orientation::default(),
+                    caption: None,
                 },
                 ContentTemplate {
+                    name: Some(String::from("right_title")),
                     area: Rectangle {
                         orig: Point{x: 0.5,y: 0.0},
                         size: Point{x: 0.5,y: 0.3},
                     },
                     font_size: 24.0,
-                    orientation: header_orientation,
+                    orientation: header_orientation.clone(),
+                    caption: None,
                 },
                 ContentTemplate {
+                    name: Some(String::from("right")),
                     area: Rectangle {
                         orig: Point{x: 0.5,y: 0.3},
                         size: Point{x: 0.5,y: 0.7},
                     },
                     font_size: 18.0,
                     orientation: Orientation::default(),
+                    caption: None,
                 },
             ],
         },
         "Two_Hor" => SlideTemplate {
             decorations: vec![],
+            background: None,
+            furniture: true,
             content: vec![
                 ContentTemplate {
+                    name: Some(String::from("top")),
                     area: Rectangle {
                         orig: Point{x: 0.0,y: 0.0},
                         size: Point{x: 1.0,y: 0.5},
                     },
                     font_size: 20.0,
                     orientation: Orientation::default(),
+                    caption: None,
                 },
                 ContentTemplate {
+                    name: Some(String::from("bottom")),
                     area: Rectangle {
                         orig: Point{x: 0.0,y: 0.5},
                         size: Point{x: 1.0,y: 0.5},
                     },
                     font_size: 20.0,
                     orientation: Orientation::default(),
+                    caption: None,
+                },
+            ],
+        },
+
+        // opens a new part of the presentation
+        "Divider" => SlideTemplate {
+            decorations: vec![
+                Decoration {
+                    area: Rectangle {
+                        orig: Point{x: 0.1,y: 0.62},
+                        size: Point{x: 0.15,y: 0.0},
+                    },
+                    shape: Shape::Line,
+                    fill: None,
+                    stroke: Some(Stroke {
+                        color: ColorRef::Index(1),
+                        width: 6.0,
+                        dash: vec![],
+                    }),
+                    image: None,
+                },
+            ],
+            background: None,
+            furniture: false,
+            content: vec![
+                ContentTemplate {
+                    name: Some(String::from("title")),
+                    area: Rectangle {
+                        orig: Point{x: 0.1,y: 0.2},
+                        size: Point{x: 0.8,y: 0.4},
+                    },
+                    font_size: 40.0,
+                    orientation: title_orientation.clone(),
+                    caption: None,
+                },
+                ContentTemplate {
+                    name: Some(String::from("subtitle")),
+                    area: Rectangle {
+                        orig: Point{x: 0.1,y: 0.66},
+                        size: Point{x: 0.8,y: 0.24},
+                    },
+                    font_size: 20.0,
+                    orientation: Orientation::default(),
+                    caption: None,
+                },
+            ],
+        },
+
+        "Quote" => SlideTemplate {
+            decorations: vec![
+                Decoration {
+                    area: Rectangle {
+                        orig: Point{x: 0.1,y: 0.15},
+                        size: Point{x: 0.0,y: 0.5},
+                    },
+                    shape: Shape::Line,
+                    fill: None,
+                    stroke: Some(Stroke {
+                        color: ColorRef::Index(1),
+                        width: 4.0,
+                        dash: vec![],
+                    }),
+                    image: None,
+                },
+            ],
+            background: None,
+            furniture: true,
+            content: vec![
+                ContentTemplate {
+                    name: Some(String::from("quote")),
+                    area: Rectangle {
+                        orig: Point{x: 0.15,y: 0.15},
+                        size: Point{x: 0.7,y: 0.5},
+                    },
+                    font_size: 28.0,
+                    orientation: quote_orientation,
+                    caption: None,
+                },
+                ContentTemplate {
+                    name: Some(String::from("author")),
+                    area: Rectangle {
+                        orig: Point{x: 0.15,y: 0.7},
+                        size: Point{x: 0.7,y: 0.15},
+                    },
+                    font_size: 18.0,
+                    orientation: author_orientation,
+                    caption: None,
+                },
+            ],
+        },
+
+        // a big number or a short sentence with an explanation below
+        "Statement" => SlideTemplate {
+            decorations: vec![
+                Decoration {
+                    area: Rectangle {
+                        orig: Point{x: 0.4,y: 0.62},
+                        size: Point{x: 0.2,y: 0.0},
+                    },
+                    shape: Shape::Line,
+                    fill: None,
+                    stroke: Some(Stroke {
+                        color: ColorRef::Index(1),
+                        width: 3.0,
+                        dash: vec![],
+                    }),
+                    image: None,
+                },
+            ],
+            background: None,
+            furniture: true,
+            content: vec![
+                ContentTemplate {
+                    name: Some(String::from("number")),
+                    area: Rectangle {
+                        orig: Point{x: 0.0,y: 0.1},
+                        size: Point{x: 1.0,y: 0.5},
+                    },
+                    font_size: 96.0,
+                    orientation: header_orientation.clone(),
+                    caption: None,
+                },
+                ContentTemplate {
+                    name: Some(String::from("statement")),
+                    area: Rectangle {
+                        orig: Point{x: 0.1,y: 0.66},
+                        size: Point{x: 0.8,y: 0.3},
+                    },
+                    font_size: 24.0,
+                    orientation: caption_orientation.clone(),
+                    caption: None,
+                },
+            ],
+        },
+
+        "Image_Caption" => SlideTemplate {
+            decorations: vec![
+                Decoration {
+                    area: Rectangle {
+                        orig: Point{x: 0.0,y: 0.22},
+                        size: Point{x: 1.0,y: 0.0},
+                    },
+                    shape: Shape::Line,
+                    fill: None,
+                    stroke: Some(Stroke {
+                        color: ColorRef::Index(1),
+                        width: 1.5,
+                        dash: vec![],
+                    }),
+                    image: None,
+                },
+            ],
+            background: None,
+            furniture: true,
+            content: vec![
+                ContentTemplate {
+                    name: Some(String::from("title")),
+                    area: Rectangle {
+                        orig: Point{x: 0.0,y: 0.0},
+                        size: Point{x: 1.0,y: 0.2},
+                    },
+                    font_size: 24.0,
+                    orientation: title_orientation.clone(),
+                    caption: None,
+                },
+                ContentTemplate {
+                    name: Some(String::from("image")),
+                    area: Rectangle {
+                        orig: Point{x: 0.0,y: 0.26},
+                        size: Point{x: 1.0,y: 0.74},
+                    },
+                    font_size: 18.0,
+                    orientation: centered.clone(),
+                    caption: Some(CaptionTemplate {
+                        position: CaptionPosition::Below,
+                        size: 0.12,
+                        font_size: 14.0,
+                        orientation: caption_orientation.clone(),
+                    }),
+                },
+            ],
+        },
+
+        // the image fills the whole drawing area, so only
+        // the margins of the style stay free
+        "Full_Image" => SlideTemplate {
+            decorations: vec![],
+            background: None,
+            furniture: false,
+            content: vec![
+                ContentTemplate {
+                    name: Some(String::from("image")),
+                    area: Rectangle {
+                        orig: Point{x: 0.0,y: 0.0},
+                        size: Point{x: 1.0,y: 1.0},
+                    },
+                    font_size: 18.0,
+                    orientation: centered,
+                    caption: None,
+                },
+            ],
+        },
+
+        "Three_Col" => SlideTemplate {
+            decorations: vec![
+                Decoration {
+                    area: Rectangle {
+                        orig: Point{x: 0.0,y: 0.22},
+                        size: Point{x: 1.0,y: 0.0},
+                    },
+                    shape: Shape::Line,
+                    fill: None,
+                    stroke: Some(Stroke {
+                        color: ColorRef::Index(1),
+                        width: 1.5,
+                        dash: vec![],
+                    }),
+                    image: None,
+                },
+                Decoration {
+                    area: Rectangle {
+                        orig: Point{x: 0.325,y: 0.3},
+                        size: Point{x: 0.0,y: 0.6},
+                    },
+                    shape: Shape::Line,
+                    fill: None,
+                    stroke: Some(Stroke {
+                        color: ColorRef::Index(1),
+                        width: 1.0,
+                        dash: vec![],
+                    }),
+                    image: None,
+                },
+                Decoration {
+                    area: Rectangle {
+                        orig: Point{x: 0.675,y: 0.3},
+                        size: Point{x: 0.0,y: 0.6},
+                    },
+                    shape: Shape::Line,
+                    fill: None,
+                    stroke: Some(Stroke {
+                        color: ColorRef::Index(1),
+                        width: 1.0,
+                        dash: vec![],
+                    }),
+                    image: None,
+                },
+            ],
+            background: None,
+            furniture: true,
+            content: vec![
+                ContentTemplate {
+                    name: Some(String::from("title")),
+                    area: Rectangle {
+                        orig: Point{x: 0.0,y: 0.0},
+                        size: Point{x: 1.0,y: 0.2},
+                    },
+                    font_size: 24.0,
+                    orientation: title_orientation.clone(),
+                    caption: None,
+                },
+                ContentTemplate {
+                    name: Some(String::from("left")),
+                    area: Rectangle {
+                        orig: Point{x: 0.0,y: 0.28},
+                        size: Point{x: 0.3,y: 0.72},
+                    },
+                    font_size: 18.0,
+                    orientation: Orientation::default(),
+                    caption: None,
+                },
+                ContentTemplate {
+                    name: Some(String::from("middle")),
+                    area: Rectangle {
+                        orig: Point{x: 0.35,y: 0.28},
+                        size: Point{x: 0.3,y: 0.72},
+                    },
+                    font_size: 18.0,
+                    orientation: Orientation::default(),
+                    caption: None,
+                },
+                ContentTemplate {
+                    name: Some(String::from("right")),
+                    area: Rectangle {
+                        orig: Point{x: 0.7,y: 0.28},
+                        size: Point{x: 0.3,y: 0.72},
+                    },
+                    font_size: 18.0,
+                    orientation: Orientation::default(),
+                    caption: None,
+                },
+            ],
+        },
+
+        // a table comparing two options, the criteria are the row headers
+        // and every line of the criteria and options is a row
+        "Comparison" => SlideTemplate {
+            decorations: vec![
+                Decoration {
+                    area: Rectangle {
+                        orig: Point{x: 0.0,y: 0.22},
+                        size: Point{x: 1.0,y: 0.0},
+                    },
+                    shape: Shape::Line,
+                    fill: None,
+                    stroke: Some(Stroke {
+                        color: ColorRef::Index(1),
+                        width: 1.5,
+                        dash: vec![],
+                    }),
+                    image: None,
+                },
+                Decoration {
+                    area: Rectangle {
+                        orig: Point{x: 0.0,y: 0.37},
+                        size: Point{x: 1.0,y: 0.0},
+                    },
+                    shape: Shape::Line,
+                    fill: None,
+                    stroke: Some(Stroke {
+                        color: ColorRef::Index(1),
+                        width: 1.0,
+                        dash: vec![],
+                    }),
+                    image: None,
+                },
+                Decoration {
+                    area: Rectangle {
+                        orig: Point{x: 0.3,y: 0.26},
+                        size: Point{x: 0.0,y: 0.7},
+                    },
+                    shape: Shape::Line,
+                    fill: None,
+                    stroke: Some(Stroke {
+                        color: ColorRef::Index(1),
+                        width: 1.0,
+                        dash: vec![],
+                    }),
+                    image: None,
+                },
+                Decoration {
+                    area: Rectangle {
+                        orig: Point{x: 0.65,y: 0.26},
+                        size: Point{x: 0.0,y: 0.7},
+                    },
+                    shape: Shape::Line,
+                    fill: None,
+                    stroke: Some(Stroke {
+                        color: ColorRef::Index(1),
+                        width: 1.0,
+                        dash: vec![],
+                    }),
+                    image: None,
+                },
+            ],
+            background: None,
+            furniture: true,
+            content: vec![
+                ContentTemplate {
+                    name: Some(String::from("title")),
+                    area: Rectangle {
+                        orig: Point{x: 0.0,y: 0.0},
+                        size: Point{x: 1.0,y: 0.2},
+                    },
+                    font_size: 24.0,
+                    orientation: title_orientation.clone(),
+                    caption: None,
+                },
+                ContentTemplate {
+                    name: Some(String::from("left_title")),
+                    area: Rectangle {
+                        orig: Point{x: 0.32,y: 0.24},
+                        size: Point{x: 0.31,y: 0.12},
+                    },
+                    font_size: 20.0,
+                    orientation: header_orientation.clone(),
+                    caption: None,
+                },
+                ContentTemplate {
+                    name: Some(String::from("right_title")),
+                    area: Rectangle {
+                        orig: Point{x: 0.67,y: 0.24},
+                        size: Point{x: 0.31,y: 0.12},
+                    },
+                    font_size: 20.0,
+                    orientation: header_orientation.clone(),
+                    caption: None,
+                },
+                ContentTemplate {
+                    name: Some(String::from("criteria")),
+                    area: Rectangle {
+                        orig: Point{x: 0.0,y: 0.4},
+                        size: Point{x: 0.28,y: 0.6},
+                    },
+                    font_size: 18.0,
+                    orientation: Orientation::default(),
+                    caption: None,
+                },
+                ContentTemplate {
+                    name: Some(String::from("left")),
+                    area: Rectangle {
+                        orig: Point{x: 0.32,y: 0.4},
+                        size: Point{x: 0.31,y: 0.6},
+                    },
+                    font_size: 18.0,
+                    orientation: Orientation::default(),
+                    caption: None,
+                },
+                ContentTemplate {
+                    name: Some(String::from("right")),
+                    area: Rectangle {
+                        orig: Point{x: 0.67,y: 0.4},
+                        size: Point{x: 0.31,y: 0.6},
+                    },
+                    font_size: 18.0,
+                    orientation: Orientation::default(),
+                    caption: None,
+                },
+            ],
+        },
+
+        // the last slide for thanks or questions and the contact
+        "Closing" => SlideTemplate {
+            decorations: vec![
+                Decoration {
+                    area: Rectangle {
+                        orig: Point{x: 0.35,y: 0.62},
+                        size: Point{x: 0.3,y: 0.0},
+                    },
+                    shape: Shape::Line,
+                    fill: None,
+                    stroke: Some(Stroke {
+                        color: ColorRef::Index(1),
+                        width: 2.0,
+                        dash: vec![],
+                    }),
+                    image: None,
+                },
+            ],
+            background: None,
+            furniture: false,
+            content: vec![
+                ContentTemplate {
+                    name: Some(String::from("title")),
+                    area: Rectangle {
+                        orig: Point{x: 0.0,y: 0.15},
+                        size: Point{x: 1.0,y: 0.45},
+                    },
+                    font_size: 48.0,
+                    orientation: header_orientation,
+                    caption: None,
+                },
+                ContentTemplate {
+                    name: Some(String::from("contact")),
+                    area: Rectangle {
+                        orig: Point{x: 0.1,y: 0.66},
+                        size: Point{x: 0.8,y: 0.24},
+                    },
+                    font_size: 20.0,
+                    orientation: caption_orientation,
+                    caption: None,
                 },
             ],
         },
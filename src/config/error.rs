@@ -0,0 +1,217 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    path::{Path, PathBuf},
+};
+
+/// a problem with a style or template
+#[derive(Debug)]
+pub enum ConfigError {
+    /// the file couldn't be read or isn't valid hjson
+    File { file: PathBuf, reason: String },
+    /// a value doesn't have the expected format or refers to something
+    /// missing, the key is the path to it like Head_Cont.template[1].orientation
+    Value {
+        file: Option<PathBuf>,
+        key: String,
+        value: String,
+        expected: String,
+    },
+    /// a file referenced at the key couldn't be found at any of the tried locations
+    Missing {
+        file: Option<PathBuf>,
+        key: String,
+        path: PathBuf,
+        tried: Vec<PathBuf>,
+    },
+}
+
+impl Error for ConfigError {}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Self::File { file, reason } => write!(f, "{}: {}", file.to_string_lossy(), reason),
+            Self::Value {
+                file,
+                key,
+                value,
+                expected,
+            } => {
+                if let Some(file) = file {
+                    write!(f, "{}: ", file.to_string_lossy())?;
+                }
+                write!(f, "{}: expected {}, found {}", key, expected, value)
+            }
+            Self::Missing {
+                file,
+                key,
+                path,
+                tried,
+            } => {
+                if let Some(file) = file {
+                    write!(f, "{}: ", file.to_string_lossy())?;
+                }
+                write!(
+                    f,
+                    "{}: couldn't find {}, tried:",
+                    key,
+                    path.to_string_lossy()
+                )?;
+                for t in tried.iter() {
+                    write!(f, "\n\t{}", t.to_string_lossy())?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl ConfigError {
+    pub fn file<S: Into<String>>(file: &Path, reason: S) -> Self {
+        Self::File {
+            file: file.to_path_buf(),
+            reason: reason.into(),
+        }
+    }
+}
+
+/// collects the problems while the values of a file are converted,
+/// so all of them are found in one pass
+pub struct Checker {
+    file: Option<PathBuf>,
+    /// the keys to the value which is currently converted
+    keys: Vec<String>,
+    errors: Vec<ConfigError>,
+}
+
+impl Checker {
+    pub fn new(file: Option<&Path>) -> Self {
+        Self {
+            file: file.map(Path::to_path_buf),
+            keys: vec![],
+            errors: vec![],
+        }
+    }
+
+    /// converts the value of the key inside the current one
+    pub fn key<K: Display, T, F: FnOnce(&mut Self) -> T>(&mut self, key: K, convert: F) -> T {
+        self.keys.push(key.to_string());
+        let value = convert(self);
+        self.keys.pop();
+        value
+    }
+
+    /// converts every item of the list under the key with its index
+    pub fn list<I, T, F>(&mut self, key: &str, list: I, mut convert: F) -> Vec<T>
+    where
+        I: IntoIterator,
+        F: FnMut(&mut Self, I::Item) -> T,
+    {
+        list.into_iter()
+            .enumerate()
+            .map(|(i, item)| self.key(format_args!("{}[{}]", key, i), |c| convert(c, item)))
+            .collect()
+    }
+
+    /// adds a problem with the value of the key inside the current one
+    pub fn invalid<V: Display, E: Into<String>>(&mut self, key: &str, value: V, expected: E) {
+        let key = self.key_path(key);
+        self.errors.push(ConfigError::Value {
+            file: self.file.clone(),
+            key,
+            value: value.to_string(),
+            expected: expected.into(),
+        });
+    }
+
+    /// adds a file referenced by the key inside the current one, which wasn't found
+    pub fn missing(&mut self, key: &str, path: &Path, tried: Vec<PathBuf>) {
+        let key = self.key_path(key);
+        self.errors.push(ConfigError::Missing {
+            file: self.file.clone(),
+            key,
+            path: path.to_path_buf(),
+            tried,
+        });
+    }
+
+    /// the keys to the value joined with the key inside of it
+    fn key_path(&self, key: &str) -> String {
+        self.keys
+            .iter()
+            .map(String::as_str)
+            .chain(Some(key).filter(|k| !k.is_empty()))
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    /// the problems which were found
+    pub fn into_errors(self) -> Vec<ConfigError> {
+        self.errors
+    }
+
+    /// the converted value if there wasn't a problem
+    pub fn finish<T>(self, value: T) -> Result<T, Vec<ConfigError>> {
+        if self.errors.is_empty() {
+            Ok(value)
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Checker;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn key_paths() {
+        let mut checker = Checker::new(Some(Path::new("style.hjson")));
+        checker.invalid("font", 1, "a name");
+        checker.key("Title", |c| {
+            c.list("decoration", &[0, 1], |c, &i| {
+                if i == 1 {
+                    c.key("stroke", |c| c.invalid("width", "a", "a number"));
+                }
+            });
+            c.missing(
+                "image",
+                Path::new("logo.svg"),
+                vec![PathBuf::from("/a/logo.svg")],
+            );
+        });
+        // an empty key is the current value itself
+        checker.key("link", |c| c.invalid("", "x", "an object"));
+
+        let messages: Vec<_> = checker
+            .into_errors()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "style.hjson: font: expected a name, found 1",
+                "style.hjson: Title.decoration[1].stroke.width: expected a number, found a",
+                "style.hjson: Title.image: couldn't find logo.svg, tried:\n\t/a/logo.svg",
+                "style.hjson: link: expected an object, found x",
+            ]
+        );
+    }
+
+    #[test]
+    fn finish() {
+        let checker = Checker::new(None);
+        assert_eq!(checker.finish(1).ok(), Some(1));
+
+        let mut checker = Checker::new(None);
+        checker.key("link", |c| c.invalid("color", 9, "an index below 3"));
+        let errors = checker.finish(1).unwrap_err();
+        assert_eq!(
+            errors[0].to_string(),
+            "link.color: expected an index below 3, found 9"
+        );
+    }
+}
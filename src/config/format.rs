@@ -0,0 +1,267 @@
+use super::error::ConfigError;
+use serde::Deserialize;
+use serde_hjson::Value;
+use std::{
+    ffi::OsStr,
+    fmt::{self, Display, Formatter},
+    path::{Path, PathBuf},
+};
+
+/// the formats a style or template can be written in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Hjson,
+    Json,
+    Toml,
+    Yaml,
+}
+
+/// the extensions of the formats, the first one is tried first
+const EXTENSIONS: [(&str, Format); 5] = [
+    ("hjson", Format::Hjson),
+    ("json", Format::Json),
+    ("toml", Format::Toml),
+    ("yaml", Format::Yaml),
+    ("yml", Format::Yaml),
+];
+
+impl Format {
+    /// the format of the file by its extension, hjson is used for unknown ones
+    pub fn of(path: &Path) -> Self {
+        path.extension()
+            .and_then(OsStr::to_str)
+            .and_then(Self::from_name)
+            .unwrap_or(Format::Hjson)
+    }
+
+    /// the format with the name or extension like yaml or yml
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        EXTENSIONS
+            .iter()
+            .find(|(e, _)| *e == name)
+            .map(|(_, format)| *format)
+    }
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        let name = match self {
+            Self::Hjson => "hjson",
+            Self::Json => "json",
+            Self::Toml => "toml",
+            Self::Yaml => "yaml",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// finds the file with the name inside the dir in any of the formats,
+/// if there isn't one the hjson file is returned
+pub fn find_file(dir: &Path, name: &str) -> PathBuf {
+    EXTENSIONS
+        .iter()
+        .map(|(ext, _)| dir.join(name).with_extension(ext))
+        .find(|p| p.exists())
+        .unwrap_or_else(|| dir.join(name).with_extension("hjson"))
+}
+
+/// parses the source of the file in the format of its extension,
+/// every format is parsed into the same value so styles
+/// and templates are read the same way in all of them
+pub fn parse(path: &Path, source: &str) -> Result<Value, ConfigError> {
+    parse_as(Format::of(path), path, source)
+}
+
+/// parses the source in the format, the path is the file it's from
+pub fn parse_as(format: Format, path: &Path, source: &str) -> Result<Value, ConfigError> {
+    let value = match format {
+        Format::Hjson => serde_hjson::from_str(source).map_err(|e| e.to_string()),
+        Format::Json => serde_json::from_str(source).map_err(|e| e.to_string()),
+        Format::Toml => parse_toml(source),
+        Format::Yaml => serde_yaml::from_str(source).map_err(|e| e.to_string()),
+    };
+
+    value.map_err(|e| ConfigError::file(path, format!("invalid {} due to: {}", format, e)))
+}
+
+fn parse_toml(source: &str) -> Result<Value, String> {
+    let mut parser = toml::Parser::new(source);
+    let table = parser.parse().ok_or_else(|| {
+        parser
+            .errors
+            .iter()
+            .map(|e| {
+                let (line, col) = parser.to_linecol(e.lo);
+                format!("{} at line {} column {}", e.desc, line + 1, col + 1)
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    })?;
+
+    let mut decoder = toml::Decoder::new(toml::Value::Table(table));
+    Value::deserialize(&mut decoder).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, Format};
+    use crate::config::{error::Checker, ColorRef, ConfigBuilder, Fill};
+    use std::path::Path;
+
+    const HJSON: &str = r#"{
+        Head: {
+            background: 2
+            decoration: [
+                {
+                    orig: {x: 0, y: 0.5}
+                    size: {x: 1, y: 0.25}
+                    color: 1
+                    stroke: {color: "%FF0000", width: 1.5, dash: [4, 2]}
+                }
+            ]
+            template: [
+                {
+                    name: title
+                    orig: {x: 0.1, y: 0.2}
+                    size: {x: 0.8, y: 0.3}
+                    fontSize: 32
+                    orientation: middle nowhere
+                }
+            ]
+        }
+    }"#;
+
+    const JSON: &str = r#"{
+        "Head": {
+            "background": 2,
+            "decoration": [{
+                "orig": {"x": 0, "y": 0.5},
+                "size": {"x": 1, "y": 0.25},
+                "color": 1,
+                "stroke": {"color": "%FF0000", "width": 1.5, "dash": [4, 2]}
+            }],
+            "template": [{
+                "name": "title",
+                "orig": {"x": 0.1, "y": 0.2},
+                "size": {"x": 0.8, "y": 0.3},
+                "fontSize": 32,
+                "orientation": "middle nowhere"
+            }]
+        }
+    }"#;
+
+    const TOML: &str = r#"
+        [Head]
+        background = 2
+
+        [[Head.decoration]]
+        orig = { x = 0, y = 0.5 }
+        size = { x = 1, y = 0.25 }
+        color = 1
+        stroke = { color = "%FF0000", width = 1.5, dash = [4, 2] }
+
+        [[Head.template]]
+        name = "title"
+        orig = { x = 0.1, y = 0.2 }
+        size = { x = 0.8, y = 0.3 }
+        fontSize = 32
+        orientation = "middle nowhere"
+    "#;
+
+    const YAML: &str = r#"
+Head:
+  background: 2
+  decoration:
+    - orig: {x: 0, y: 0.5}
+      size: {x: 1, y: 0.25}
+      color: 1
+      stroke: {color: "%FF0000", width: 1.5, dash: [4, 2]}
+  template:
+    - name: title
+      orig: {x: 0.1, y: 0.2}
+      size: {x: 0.8, y: 0.3}
+      fontSize: 32
+      orientation: middle nowhere
+"#;
+
+    #[test]
+    fn formats_by_extension() {
+        assert_eq!(Format::of(Path::new("a/style.toml")), Format::Toml);
+        assert_eq!(Format::of(Path::new("style.YML")), Format::Yaml);
+        assert_eq!(Format::of(Path::new("style.json")), Format::Json);
+        assert_eq!(Format::of(Path::new("style.conf")), Format::Hjson);
+        assert_eq!(Format::from_name("yaml"), Some(Format::Yaml));
+        assert_eq!(Format::from_name("ini"), None);
+    }
+
+    #[test]
+    fn same_template_in_every_format() {
+        let sources = [
+            ("template.hjson", HJSON),
+            ("template.json", JSON),
+            ("template.toml", TOML),
+            ("template.yaml", YAML),
+        ];
+
+        for (file, source) in sources.iter() {
+            let file = Path::new(file);
+            let value = parse(file, source).unwrap_or_else(|e| panic!("{}", e));
+            let mut checker = Checker::new(Some(file));
+            let entries = ConfigBuilder::default()
+                .convert_templates(value, file, &mut checker)
+                .unwrap_or_else(|e| panic!("{}", e));
+
+            // the invalid orientation is reported with the path to it
+            let errors: Vec<_> = checker
+                .into_errors()
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            assert_eq!(errors.len(), 1, "{}: {:?}", file.display(), errors);
+            let key = format!("{}: Head.template[0].orientation: ", file.display());
+            assert!(errors[0].starts_with(&key), "{}", errors[0]);
+
+            let (name, entry) = &entries[0];
+            let template = &entry.template;
+            assert_eq!(name, "Head");
+            // integers are palette indices
+            assert!(matches!(template.background, Some(ColorRef::Index(2))));
+            let decoration = &template.decorations[0];
+            assert!(matches!(
+                decoration.fill,
+                Some(Fill::Color(ColorRef::Index(1)))
+            ));
+            // integers can be used as floats
+            assert_eq!(decoration.area.orig.x, 0.0);
+            assert_eq!(decoration.area.size.y, 0.25);
+            let stroke = decoration.stroke.as_ref().unwrap();
+            assert_eq!((stroke.width, &stroke.dash), (1.5, &vec![4, 2]));
+            assert!(matches!(stroke.color, ColorRef::Color(_)));
+            let content = &template.content[0];
+            assert_eq!(content.name.as_deref(), Some("title"));
+            assert_eq!(content.font_size, 32.0);
+            assert_eq!(content.area.size.x, 0.8);
+        }
+    }
+
+    #[test]
+    fn syntax_errors_name_the_format() {
+        let sources = [
+            ("a.hjson", "{ a: [ }"),
+            ("a.json", "{\"a\": }"),
+            ("a.toml", "a = = 1"),
+            ("a.yaml", "a: [1, 2"),
+        ];
+
+        for (file, source) in sources.iter() {
+            let error = parse(Path::new(file), source).unwrap_err().to_string();
+            let format = Format::of(Path::new(file));
+            let start = format!("{}: invalid {} due to: ", file, format);
+            assert!(error.starts_with(&start), "{}", error);
+        }
+        // toml reports where the problem is
+        let error = parse(Path::new("a.toml"), "a = 1\nb = = 2").unwrap_err();
+        assert!(error.to_string().contains("at line 2"), "{}", error);
+    }
+}
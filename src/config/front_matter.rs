@@ -0,0 +1,89 @@
+use super::{
+    error::ConfigError,
+    format::{self, Format},
+};
+use serde_hjson::{Map, Value};
+use std::path::PathBuf;
+
+/// the style and templates at the start of a presentation
+#[derive(Debug)]
+pub struct FrontMatter {
+    /// the presentation, paths inside the front matter are relative to it
+    pub file: PathBuf,
+    /// the name of the format, hjson if it's none
+    pub format: Option<String>,
+    pub source: String,
+}
+
+/// the parsed front matter
+#[derive(Debug)]
+pub struct Inline {
+    pub file: PathBuf,
+    /// merged on top of the given style
+    pub style: Option<Map<String, Value>>,
+    /// override the templates with the same name of the given ones
+    pub templates: Option<Value>,
+}
+
+impl FrontMatter {
+    /// parses the front matter, the problems are added to the errors
+    pub fn parse(&self, errors: &mut Vec<ConfigError>) -> Inline {
+        let mut inline = Inline {
+            file: self.file.clone(),
+            style: None,
+            templates: None,
+        };
+        let invalid = |key: &str, value: &str, expected: &str| ConfigError::Value {
+            file: Some(self.file.clone()),
+            key: String::from(key),
+            value: String::from(value),
+            expected: String::from(expected),
+        };
+
+        let format = match self.format.as_deref() {
+            Some(name) => match Format::from_name(name) {
+                Some(format) => format,
+                None => {
+                    errors.push(invalid("+++", name, "hjson, json, toml or yaml"));
+                    return inline;
+                }
+            },
+            None => Format::Hjson,
+        };
+
+        let map = match format::parse_as(format, &self.file, &self.source) {
+            Ok(Value::Object(map)) => map,
+            Ok(_) => {
+                errors.push(ConfigError::file(
+                    &self.file,
+                    "the front matter needs to be an object",
+                ));
+                return inline;
+            }
+            Err(e) => {
+                errors.push(e);
+                return inline;
+            }
+        };
+
+        for (key, value) in map {
+            match (key.as_str(), value) {
+                ("style", Value::Object(style)) => inline.style = Some(style),
+                ("style", value) => errors.push(invalid("style", &describe(&value), "an object")),
+                ("templates", templates) => inline.templates = Some(templates),
+                (key, value) => errors.push(invalid(key, &describe(&value), "style or templates")),
+            }
+        }
+
+        inline
+    }
+}
+
+/// the value for messages, objects and lists would be too long
+fn describe(value: &Value) -> String {
+    match value {
+        Value::Object(_) => String::from("an object"),
+        Value::Array(_) => String::from("a list"),
+        value => value.to_string(),
+    }
+}
@@ -0,0 +1,193 @@
+use super::{default::default_slide_templates, error::ConfigError, SlideTemplate, TemplateMap};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// a parsed template which may still inherit from another one
+pub struct TemplateEntry {
+    pub template: SlideTemplate,
+    /// the name of the template it extends
+    pub extends: Option<String>,
+    /// if it's none the furniture setting is inherited
+    pub furniture: Option<bool>,
+    /// the file it's written in
+    pub file: PathBuf,
+}
+
+impl SlideTemplate {
+    /// takes the decorations, slots and background of the parent, the own decorations
+    /// are drawn after the inherited ones and slots with the same name replace them
+    fn inherit(&mut self, parent: &SlideTemplate) {
+        let mut content = parent.content.clone();
+        for slot in self.content.drain(..) {
+            let same = content
+                .iter_mut()
+                .find(|c| c.name.is_some() && c.name == slot.name);
+
+            match same {
+                Some(inherited) => *inherited = slot,
+                None => content.push(slot),
+            }
+        }
+        self.content = content;
+
+        self.background = self.background.take().or_else(|| parent.background.clone());
+
+        let mut decorations = parent.decorations.clone();
+        decorations.append(&mut self.decorations);
+        self.decorations = decorations;
+    }
+}
+
+/// applies the inheritance of the templates, they can extend each other and
+/// the built-in templates. A template extending its own name extends the built-in one
+pub fn resolve(mut entries: HashMap<String, TemplateEntry>) -> Result<TemplateMap, ConfigError> {
+    let builtin = default_slide_templates();
+    let mut resolved = TemplateMap::new();
+    let names: Vec<_> = entries.keys().cloned().collect();
+
+    for name in names {
+        resolve_entry(&name, &mut entries, &builtin, &mut resolved, &mut vec![])?;
+    }
+
+    Ok(resolved)
+}
+
+/// resolves the template and everything it extends, the chain
+/// contains the templates which are currently resolved to find cycles
+fn resolve_entry(
+    name: &str,
+    entries: &mut HashMap<String, TemplateEntry>,
+    builtin: &TemplateMap,
+    resolved: &mut TemplateMap,
+    chain: &mut Vec<String>,
+) -> Result<(), ConfigError> {
+    // it's already resolved
+    let entry = match entries.remove(name) {
+        Some(entry) => entry,
+        None => return Ok(()),
+    };
+
+    let mut template = entry.template;
+    let mut furniture = entry.furniture;
+    let file = entry.file.as_path();
+
+    if let Some(parent_name) = &entry.extends {
+        let parent = if parent_name == name {
+            builtin.get(parent_name)
+        } else {
+            if chain.iter().any(|n| n == parent_name) {
+                let cycle = chain
+                    .iter()
+                    .map(String::as_str)
+                    .chain([name, parent_name])
+                    .collect::<Vec<_>>()
+                    .join(" -> ");
+                return Err(extends_error(
+                    file,
+                    name,
+                    parent_name,
+                    format!("a template outside of the cycle {}", cycle),
+                ));
+            }
+
+            chain.push(String::from(name));
+            resolve_entry(parent_name, entries, builtin, resolved, chain)?;
+            chain.pop();
+
+            resolved
+                .get(parent_name)
+                .or_else(|| builtin.get(parent_name))
+        };
+
+        let parent = parent
+            .ok_or_else(|| extends_error(file, name, parent_name, "the name of a template"))?;
+        template.inherit(parent);
+        furniture = furniture.or(Some(parent.furniture));
+    }
+
+    template.furniture = furniture.unwrap_or(true);
+    resolved.insert(String::from(name), template);
+
+    Ok(())
+}
+
+fn extends_error<E: Into<String>>(
+    file: &Path,
+    name: &str,
+    parent: &str,
+    expected: E,
+) -> ConfigError {
+    ConfigError::Value {
+        file: Some(file.to_path_buf()),
+        key: format!("{}.extends", name),
+        value: String::from(parent),
+        expected: expected.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{ConfigError, SlideTemplate};
+    use super::TemplateEntry;
+    use std::{collections::HashMap, path::PathBuf};
+
+    fn entry(extends: Option<&str>) -> TemplateEntry {
+        TemplateEntry {
+            template: SlideTemplate {
+                decorations: vec![],
+                background: None,
+                content: vec![],
+                furniture: true,
+            },
+            extends: extends.map(String::from),
+            furniture: None,
+            file: PathBuf::from("template.hjson"),
+        }
+    }
+
+    fn error_key(error: ConfigError) -> (Option<PathBuf>, String) {
+        match error {
+            ConfigError::Value { file, key, .. } => (file, key),
+            e => panic!("expected a value error, found {}", e),
+        }
+    }
+
+    #[test]
+    fn extends_builtin_and_other_templates() {
+        let entries: HashMap<_, _> = crate::map! {
+            "Title" => entry(Some("Title")),
+            "Child" => entry(Some("Title")),
+        };
+        let resolved = super::resolve(entries).unwrap();
+
+        // the title slide of the built-ins has no furniture
+        assert!(!resolved["Title"].furniture);
+        assert!(!resolved["Child"].furniture);
+        assert_eq!(resolved["Child"].content.len(), 2);
+    }
+
+    #[test]
+    fn extends_cycle() {
+        let entries: HashMap<_, _> = crate::map! {
+            "A" => entry(Some("B")),
+            "B" => entry(Some("A")),
+        };
+        let (file, key) = error_key(super::resolve(entries).unwrap_err());
+
+        assert_eq!(file, Some(PathBuf::from("template.hjson")));
+        assert!(key == "A.extends" || key == "B.extends");
+    }
+
+    #[test]
+    fn extends_missing_parent() {
+        let entries: HashMap<_, _> = crate::map! {
+            "A" => entry(Some("Missing")),
+        };
+        let (file, key) = error_key(super::resolve(entries).unwrap_err());
+
+        assert_eq!(file, Some(PathBuf::from("template.hjson")));
+        assert_eq!(key, "A.extends");
+    }
+}
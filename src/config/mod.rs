@@ -1,46 +1,212 @@
 use crate::drawing::error::DrawError;
-use std::borrow::Cow;
-use std::collections::HashMap;
+use crate::util::pdf::Size;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::{fs, io};
 pub type TemplateMap = HashMap<String, SlideTemplate>;
 
 mod de_se;
 mod default;
+mod error;
+mod format;
+mod front_matter;
+mod inherit;
+mod overrides;
+mod paths;
 mod primitives;
+mod style;
 
+pub use error::ConfigError;
+pub use format::find_file;
+pub use front_matter::FrontMatter;
+pub use paths::locate;
 pub use primitives::*;
 
+use self::de_se::Convert;
 use self::default::default_slide_templates;
+use self::error::Checker;
+use self::front_matter::Inline;
+use self::inherit::TemplateEntry;
+use serde_hjson::{Map, Value};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Decoration {
     pub area: Rectangle<f64>,
-    pub color_idx: usize,
+    pub shape: Shape,
+    /// how the shape is filled, if it's none the shape isn't filled
+    pub fill: Option<Fill>,
+    /// the outline of the shape, if it's none it has no outline
+    pub stroke: Option<Stroke>,
+    /// an image drawn into the area over the shape, like a logo
+    pub image: Option<PathBuf>,
 }
 
-#[derive(Debug)]
+/// the form of a decoration inside its area
+#[derive(Debug, Clone)]
+pub enum Shape {
+    Rectangle,
+    /// the corners are rounded by the radius,
+    /// it's a share of the shorter side
+    RoundedRectangle(f64),
+    Ellipse,
+    /// goes from the top-left to the bottom-right corner
+    Line,
+    /// the points are relative to the area
+    Polygon(Vec<Point<f64>>),
+}
+
+/// the paint inside of a decoration
+#[derive(Debug, Clone)]
+pub enum Fill {
+    Color(ColorRef),
+    /// blends between the colors along the angle in degrees,
+    /// 0 goes from left to right, 90 from bottom to top
+    Linear {
+        from: ColorRef,
+        to: ColorRef,
+        angle: f64,
+    },
+    /// blends from the center to the edges of the area
+    Radial {
+        from: ColorRef,
+        to: ColorRef,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct Stroke {
+    pub color: ColorRef,
+    /// the width in pt
+    pub width: f64,
+    /// alternating lengths of dashes and gaps in pt,
+    /// if it's empty the line is solid
+    pub dash: Vec<i64>,
+}
+
+#[derive(Debug, Clone)]
 pub struct ContentTemplate {
+    /// the name contents can use to be put into this slot
+    pub name: Option<String>,
     pub area: Rectangle<f64>,
     pub font_size: f32,
     pub orientation: Orientation,
+    /// how the description of an image inside this area is drawn,
+    /// if it's none the description is only used as alternative text
+    pub caption: Option<CaptionTemplate>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+pub struct CaptionTemplate {
+    pub position: CaptionPosition,
+    /// the share of the content area height used by the caption
+    pub size: f64,
+    pub font_size: f32,
+    pub orientation: Orientation,
+}
+
+#[derive(Debug, Clone)]
 pub struct SlideTemplate {
     /// a decoration for the slides
     /// draws a simple rectangle at the given position(item0) with the color from the index
     pub decorations: Vec<Decoration>,
+    /// the color the whole slide is filled with, before the decorations
+    pub background: Option<ColorRef>,
     /// an area were content can appear
     pub content: Vec<ContentTemplate>,
+    /// if the furniture of the style is drawn on these slides
+    pub furniture: bool,
 }
 
 #[derive(Debug)]
 pub struct PresentStyle {
     pub colors: Vec<Color>,
+    /// colors which can be used by their name, they're
+    /// either an index into the colors or a color themselves
+    pub named_colors: HashMap<String, ColorRef>,
     pub font: String,
     pub margin: Rectangle<f64>,
     line_spacing: f64,
+    pub image: ImageStyle,
+    /// the size of every page, the templates are relative to it
+    pub page_size: Size,
+    pub link: LinkStyle,
+    /// texts drawn on every slide, like slide numbers or footers
+    pub furniture: Vec<Furniture>,
+    /// the color for text revealed in an earlier build
+    /// step, if it's none the text isn't dimmed
+    pub dim_color: Option<ColorRef>,
+}
+
+/// a text drawn on every slide after its content
+#[derive(Debug, Clone)]
+pub struct Furniture {
+    pub area: Rectangle<f64>,
+    pub font_size: f32,
+    pub orientation: Orientation,
+    /// if it's none the foreground is used
+    pub color: Option<ColorRef>,
+    /// the text with the placeholders {slide}, {total},
+    /// {title}, {date} and {section} in it
+    pub text: String,
+}
+
+/// how links inside the text are drawn
+#[derive(Debug, Clone)]
+pub struct LinkStyle {
+    /// if it's none the foreground is used
+    pub color: Option<ColorRef>,
+    pub underline: bool,
+}
+
+impl Default for LinkStyle {
+    fn default() -> Self {
+        Self {
+            color: None,
+            underline: true,
+        }
+    }
+}
+
+/// how raster images are embedded into the pdf
+#[derive(Debug, Clone)]
+pub struct ImageStyle {
+    /// images with a higher resolution inside their area are scaled down
+    pub max_dpi: u16,
+    pub compression: ImageCompression,
+    /// the quality of jpeg compressed images from 1 to 100
+    pub quality: u8,
+}
+
+impl Default for ImageStyle {
+    fn default() -> Self {
+        Self {
+            max_dpi: 300,
+            compression: ImageCompression::Flate,
+            quality: 85,
+        }
+    }
+}
+
+/// image settings which replace the ones of the style if they're given
+#[derive(Debug, Default)]
+pub struct ImageOptions {
+    pub max_dpi: Option<u16>,
+    pub compression: Option<ImageCompression>,
+    pub quality: Option<u8>,
+}
+
+impl ImageOptions {
+    pub fn apply(&self, style: &mut ImageStyle) {
+        if let Some(max_dpi) = self.max_dpi {
+            style.max_dpi = max_dpi;
+        }
+        if let Some(compression) = self.compression {
+            style.compression = compression;
+        }
+        if let Some(quality) = self.quality {
+            style.quality = quality;
+        }
+    }
 }
 
 impl Default for PresentStyle {
@@ -51,20 +217,40 @@ impl Default for PresentStyle {
                 Color::new(1.0, 0.0, 0.0),
                 Color::new(0.0, 1.0, 1.0),
             ],
+            named_colors: HashMap::new(),
             margin: Rectangle {
                 orig: Point { x: 0.05, y: 0.05 },
                 size: Point { x: 0.9, y: 0.9 },
             },
             font: String::from("Noto Sans"),
             line_spacing: 1.0,
+            image: Default::default(),
+            page_size: Size::Px(1920, 1080),
+            link: Default::default(),
+            furniture: vec![],
+            dim_color: None,
         }
     }
 }
 
+/// draws multiple slides onto a4 pages for printing
+#[derive(Debug, Clone, Copy)]
+pub struct Handout {
+    /// either 2, 4 or 6
+    pub slides_per_page: u8,
+    /// if lines for notes are drawn next to the slides
+    pub notes: bool,
+}
+
 #[derive(Default)]
 pub struct ConfigBuilder {
     pub style: Option<PathBuf>,
     pub templates: Option<Vec<PathBuf>>,
+    pub image: ImageOptions,
+    pub handout: Option<Handout>,
+    pub font_dirs: Vec<PathBuf>,
+    pub front_matter: Option<FrontMatter>,
+    pub search_paths: Vec<PathBuf>,
 }
 
 impl ConfigBuilder {
@@ -78,83 +264,226 @@ impl ConfigBuilder {
         self
     }
 
-    fn get_style(&self) -> PresentStyle {
-        let style: Result<_, Cow<_>> = self
-            .style
-            .as_ref()
-            .ok_or("no style given".into())
-            .map(|s| {
-                let r = get_reader(s).map_err(|_| Cow::Borrowed(""))?;
-                let json: de_se::StyleJson = serde_hjson::from_reader(r).map_err(|e| {
-                    Cow::Owned(format!(
-                        "invalid style format: {}, due to: {}\n",
-                        s.to_string_lossy(),
-                        e
-                    ))
-                })?;
-                Ok(PresentStyle::from(json))
-            })
-            .flatten();
+    /// image settings which are used instead of the ones from the style
+    pub fn with_image_options(mut self, options: ImageOptions) -> Self {
+        self.image = options;
+        self
+    }
 
-        if let Err(e) = &style {
-            eprintln!("{}\tusing default style instead", e);
-        }
+    /// draws the slides onto handout pages instead of a page for every slide
+    pub fn with_handout(mut self, handout: Option<Handout>) -> Self {
+        self.handout = handout;
+        self
+    }
+
+    /// directories with fonts, which can be used besides the system fonts
+    pub fn with_font_dirs(mut self, dirs: Vec<PathBuf>) -> Self {
+        self.font_dirs = dirs;
+        self
+    }
+
+    /// directories where images and styles are searched,
+    /// if they aren't relative to the file using them
+    pub fn with_search_paths(mut self, dirs: Vec<PathBuf>) -> Self {
+        self.search_paths = dirs;
+        self
+    }
+
+    /// the style and templates inside of the presentation,
+    /// they're used on top of the given style and templates
+    pub fn with_front_matter(mut self, front_matter: Option<FrontMatter>) -> Self {
+        self.front_matter = front_matter;
+        self
+    }
+
+    fn get_style(&self, inline: Option<&Inline>) -> PresentStyle {
+        let style = match (
+            &self.style,
+            inline.and_then(|i| Some((&i.file, i.style.clone()?))),
+        ) {
+            (_, Some((file, map))) => Self::merge_inline_style(self.style.as_deref(), file, map),
+            (Some(path), None) => style::load_style(path),
+            (None, None) => Err(vec![]),
+        };
 
-        style.unwrap_or_default()
-    }
-
-    fn parse_templates<'a, I: Iterator<Item = &'a PathBuf> + 'a>(
-        paths: I,
-    ) -> impl Iterator<Item = Result<impl Iterator<Item = (String, SlideTemplate)>, String>> + 'a
-    {
-        paths
-            .filter_map(|p| Some((p, get_reader(p).ok()?))) // only valid files are processed
-            .map(|(p, r)| {
-                let json: de_se::TemplateJson = serde_hjson::from_reader(r).map_err(|e| {
-                    format!(
-                        "invalid template at: {} due to:\n{}",
-                        p.to_string_lossy(),
-                        e
-                    )
-                })?;
-
-                Ok(json.into_iter().map(|(k, t)| (k, SlideTemplate::from(t))))
+        style.unwrap_or_else(|errors| {
+            print_errors(&errors);
+            if errors.is_empty() {
+                eprint!("no style given");
+            }
+            eprintln!("\tusing default style instead");
+            PresentStyle::default()
+        })
+    }
+
+    /// merges the style of the front matter on top of the given one,
+    /// if there's none or it can't be read the default style is used instead
+    fn merge_inline_style(
+        base: Option<&Path>,
+        file: &Path,
+        map: Map<String, Value>,
+    ) -> Result<PresentStyle, Vec<ConfigError>> {
+        let value = style::extend(map, file, &mut vec![]).map_err(|e| vec![e])?;
+
+        let mut style = match base.map(style::read_style) {
+            Some(Ok(base)) => base,
+            Some(Err(e)) => {
+                print_errors(&[e]);
+                style::default_value()
+            }
+            None => style::default_value(),
+        };
+        style::merge(&mut style, value);
+
+        style::convert_style(style, file)
+    }
+
+    /// parses the templates of the file and checks their values,
+    /// the problems are added to the errors
+    fn parse_templates(
+        &self,
+        path: &Path,
+        errors: &mut Vec<ConfigError>,
+    ) -> Vec<(String, TemplateEntry)> {
+        // only valid files are processed
+        let source = match read_file(path) {
+            Ok(source) => source,
+            Err(_) => return vec![],
+        };
+        let mut checker = Checker::new(Some(path));
+        let entries = format::parse(path, &source)
+            .and_then(|value| self.convert_templates(value, path, &mut checker));
+
+        errors.append(&mut checker.into_errors());
+        entries.unwrap_or_else(|e| {
+            errors.push(e);
+            vec![]
+        })
+    }
+
+    /// converts the templates inside the value, which is from the file
+    fn convert_templates(
+        &self,
+        value: Value,
+        file: &Path,
+        checker: &mut Checker,
+    ) -> Result<Vec<(String, TemplateEntry)>, ConfigError> {
+        // images are relative to the file or inside the search paths
+        let dir = file.parent().unwrap_or_else(|| Path::new(""));
+        let json: de_se::TemplateJson = serde_hjson::from_value(value).map_err(|e| {
+            ConfigError::file(file, format!("invalid template format due to:\n{}", e))
+        })?;
+
+        let entries = json
+            .into_iter()
+            .map(|(k, mut t)| {
+                let extends = t.extends.take();
+                let furniture = t.furniture;
+                let template = checker.key(&k, |c| {
+                    let mut template = t.convert(c);
+                    for (i, d) in template.decorations.iter_mut().enumerate() {
+                        let image = match d.image.take() {
+                            Some(image) => image,
+                            None => continue,
+                        };
+                        match paths::locate(&image, dir, &self.search_paths) {
+                            Ok(image) => d.image = Some(image),
+                            Err(tried) => {
+                                c.missing(&format!("decoration[{}].image", i), &image, tried)
+                            }
+                        }
+                    }
+                    template
+                });
+
+                let entry = TemplateEntry {
+                    template,
+                    extends,
+                    furniture,
+                    file: file.to_path_buf(),
+                };
+                (k, entry)
             })
+            .collect();
+
+        Ok(entries)
     }
 
-    fn get_templates(&self) -> TemplateMap {
-        let map: Result<_, Cow<str>> = try {
-            let paths = self
-                .templates
-                .as_ref()
-                .ok_or(Cow::Borrowed("no templates given"))?;
+    fn get_templates(&self, inline: Option<&Inline>) -> TemplateMap {
+        let map: Result<_, Vec<ConfigError>> = try {
+            let paths = self.templates.as_ref().ok_or_else(Vec::new)?;
+
+            // the problems of every file are collected before giving up
+            let mut entries = HashMap::new();
+            let mut errors = vec![];
+            for path in paths.iter() {
+                entries.extend(self.parse_templates(path, &mut errors));
+            }
+
+            if let Some((file, templates)) =
+                inline.and_then(|i| Some((&i.file, i.templates.clone()?)))
+            {
+                let mut checker = Checker::new(Some(file));
+                let inline_entries =
+                    checker.key("templates", |c| self.convert_templates(templates, file, c));
+                errors.append(&mut checker.into_errors());
+                match inline_entries {
+                    Ok(e) => entries.extend(e),
+                    Err(e) => errors.push(e),
+                }
+            }
 
-            Self::parse_templates(paths.iter()).try_fold(TemplateMap::new(), |mut map, t| {
-                map.extend(t?);
-                Ok::<_, String>(map)
-            })?
+            match inherit::resolve(entries) {
+                Ok(map) if errors.is_empty() => map,
+                Ok(_) => Err(errors)?,
+                Err(e) => {
+                    errors.push(e);
+                    Err(errors)?
+                }
+            }
         };
 
-        map.map(|m| {
+        map.and_then(|m| {
             // if nothing is read it's a error too
             if m.is_empty() {
-                Err(Cow::Borrowed(""))
+                Err(vec![])
             } else {
                 Ok(m)
             }
         })
-        .flatten()
-        .unwrap_or_else(|e| {
-            eprintln!("{}\n\tusing default template", e);
+        .map(|mut m| {
+            // the built-in templates can be used too,
+            // unless a read template has the same name
+            for (name, template) in default_slide_templates() {
+                m.entry(name).or_insert(template);
+            }
+            m
+        })
+        .unwrap_or_else(|errors| {
+            print_errors(&errors);
+            eprintln!("\tusing default template");
             default_slide_templates()
         })
     }
 
     pub fn build(self, doc_name: &'_ str) -> Config<'_> {
+        let mut errors = vec![];
+        let inline = self.front_matter.as_ref().map(|f| f.parse(&mut errors));
+        if !errors.is_empty() {
+            print_errors(&errors);
+            eprintln!("\tignoring these parts of the front matter");
+        }
+
+        let mut style = self.get_style(inline.as_ref());
+        self.image.apply(&mut style.image);
+
         Config {
-            style: self.get_style(),
-            slide_templates: self.get_templates(),
+            style,
+            slide_templates: self.get_templates(inline.as_ref()),
             doc_name,
+            handout: self.handout,
+            font_dirs: self.font_dirs,
+            search_paths: self.search_paths,
         }
     }
 }
@@ -164,13 +493,26 @@ pub struct Config<'a> {
     pub style: PresentStyle,
     pub slide_templates: TemplateMap,
     pub doc_name: &'a str,
+    /// if it's some the slides are drawn onto handout pages
+    pub handout: Option<Handout>,
+    /// directories with fonts besides the system fonts
+    pub font_dirs: Vec<PathBuf>,
+    /// directories where images and styles are searched
+    pub search_paths: Vec<PathBuf>,
 }
 
-fn get_reader<P: AsRef<Path>>(path: P) -> io::Result<io::BufReader<fs::File>> {
-    let file = fs::File::open(path.as_ref());
+/// prints every problem found in the config
+fn print_errors(errors: &[ConfigError]) {
+    for e in errors.iter() {
+        eprintln!("{}", e);
+    }
+}
 
-    match file {
-        Ok(f) => Ok(io::BufReader::new(f)),
+fn read_file<P: AsRef<Path>>(path: P) -> io::Result<String> {
+    let source = fs::read_to_string(path.as_ref());
+
+    match source {
+        Ok(source) => Ok(source),
         Err(e) => {
             eprintln!("{}: {}", path.as_ref().to_string_lossy(), e);
             Err(e)
@@ -184,17 +526,155 @@ impl<'a> Config<'a> {
     }
 
     /// change the style to the one specified inside the path
-    pub fn change_style<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
-        let json: de_se::StyleJson = serde_hjson::from_reader(get_reader(path)?).unwrap();
-        self.style = json.into();
+    pub fn change_style<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Vec<ConfigError>> {
+        self.style = style::load_style(path.as_ref())?;
         Ok(())
     }
 
-    pub fn get_color(&self, idx: usize) -> Result<Color, DrawError> {
-        self.style
-            .colors
-            .get(idx)
-            .ok_or(DrawError::NoColor(idx))
-            .map(|c| *c)
+    pub fn get_color(&self, color: &ColorRef) -> Result<Color, DrawError> {
+        let color = match color {
+            ColorRef::Name(name) => match self.style.named_colors.get(name) {
+                // named colors can't refer to other names
+                Some(ColorRef::Name(_)) | None => {
+                    return Err(DrawError::NoNamedColor(name.clone()))
+                }
+                Some(color) => color,
+            },
+            color => color,
+        };
+
+        match color {
+            ColorRef::Index(idx) => self
+                .style
+                .colors
+                .get(*idx)
+                .copied()
+                .ok_or(DrawError::NoColor(*idx)),
+            ColorRef::Color(c) => Ok(*c),
+            ColorRef::Name(_) => unreachable!("the names are resolved above"),
+        }
+    }
+
+    /// the color of the text, if a content doesn't have another one
+    pub fn foreground(&self) -> Result<Color, DrawError> {
+        self.get_color(&ColorRef::Index(1))
+    }
+
+    /// checks if every color used by the style and the templates of the kinds exists,
+    /// returns a problem for every missing color
+    pub fn check_colors(&self, kinds: &HashSet<String>) -> Vec<ConfigError> {
+        let style = &self.style;
+        let mut used: Vec<(String, &ColorRef)> = vec![];
+
+        used.extend(
+            style
+                .named_colors
+                .iter()
+                .map(|(name, c)| (format!("namedColors.{}", name), c)),
+        );
+        used.extend(
+            style
+                .link
+                .color
+                .iter()
+                .map(|c| (String::from("link.color"), c)),
+        );
+        used.extend(
+            style
+                .dim_color
+                .iter()
+                .map(|c| (String::from("dimColor"), c)),
+        );
+        used.extend(
+            style
+                .furniture
+                .iter()
+                .enumerate()
+                .filter_map(|(i, f)| Some((format!("furniture[{}].color", i), f.color.as_ref()?))),
+        );
+
+        // unused templates, like most of the built-in ones, don't matter
+        let templates = self
+            .slide_templates
+            .iter()
+            .filter(|(name, _)| kinds.contains(*name));
+        for (name, template) in templates {
+            if let Some(c) = &template.background {
+                used.push((format!("{}.background", name), c));
+            }
+            for (i, d) in template.decorations.iter().enumerate() {
+                let key = format!("{}.decoration[{}]", name, i);
+                match &d.fill {
+                    Some(Fill::Color(c)) => used.push((format!("{}.color", key), c)),
+                    Some(Fill::Linear { from, to, .. }) | Some(Fill::Radial { from, to }) => {
+                        used.push((format!("{}.gradient.from", key), from));
+                        used.push((format!("{}.gradient.to", key), to));
+                    }
+                    None => {}
+                }
+                if let Some(s) = &d.stroke {
+                    used.push((format!("{}.stroke.color", key), &s.color));
+                }
+            }
+        }
+
+        used.into_iter()
+            .filter(|(_, color)| self.get_color(color).is_err())
+            .map(|(key, color)| {
+                let (value, expected) = match color {
+                    ColorRef::Index(idx) => (
+                        idx.to_string(),
+                        format!("an index below {}", style.colors.len()),
+                    ),
+                    ColorRef::Name(name) => (
+                        name.clone(),
+                        String::from("the name of a color in namedColors"),
+                    ),
+                    ColorRef::Color(_) => unreachable!("colors always exist"),
+                };
+
+                ConfigError::Value {
+                    file: None,
+                    key,
+                    value,
+                    expected,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConfigBuilder, PresentStyle};
+    use serde_hjson::{Map, Value};
+    use std::path::Path;
+
+    fn inline(source: &str) -> Map<String, Value> {
+        serde_hjson::from_str(source).unwrap()
+    }
+
+    #[test]
+    fn inline_style_without_base() {
+        let file = Path::new("present");
+        let default = PresentStyle::default();
+
+        for base in [None, Some(Path::new("missing/style.hjson"))] {
+            let style = ConfigBuilder::merge_inline_style(base, file, inline(r#"{font: "Sans"}"#))
+                .expect("the inline style is merged onto the default one");
+
+            assert_eq!(style.font, "Sans");
+            assert_eq!(style.colors.len(), default.colors.len());
+            assert_eq!(style.colors[1].r, default.colors[1].r);
+            assert_eq!(style.line_spacing, default.line_spacing);
+            assert_eq!(style.margin.size.x, default.margin.size.x);
+        }
+    }
+
+    #[test]
+    fn inline_style_keeps_the_default_valid() {
+        let style = ConfigBuilder::merge_inline_style(None, Path::new("present"), inline("{}"));
+
+        assert!(style.is_ok());
     }
 }
@@ -0,0 +1,186 @@
+use super::{
+    de_se::{convert_orientation, ColorJson, Convert},
+    error::{Checker, ConfigError},
+    SlideTemplate,
+};
+
+const KEYS_ERR: &str = "background, hide, <slot>.fontSize or <slot>.orientation";
+
+impl SlideTemplate {
+    /// applies the attributes of a slide onto a copy of the template. They can
+    /// change the background, hide decorations by their index and change the
+    /// font size or orientation of a slot by its name or index. The invalid
+    /// attributes are skipped and returned as problems
+    pub fn with_overrides(&self, attributes: &[(String, String)]) -> (Self, Vec<ConfigError>) {
+        let mut template = self.clone();
+        let mut hidden = vec![];
+        let mut checker = Checker::new(None);
+
+        for (key, value) in attributes.iter() {
+            match key.split_once('.') {
+                None if key == "background" => {
+                    let color = match value.parse() {
+                        Ok(idx) => ColorJson::Index(idx),
+                        Err(_) => ColorJson::Text(value.clone()),
+                    };
+                    template.background = Some(checker.key(key, |c| color.convert(c)));
+                }
+                None if key == "hide" => {
+                    for idx in value.split(',') {
+                        match idx.trim().parse::<usize>() {
+                            Ok(idx) if idx < template.decorations.len() => hidden.push(idx),
+                            _ => checker.invalid(
+                                key,
+                                idx,
+                                format!(
+                                    "the index of a decoration below {}",
+                                    template.decorations.len()
+                                ),
+                            ),
+                        }
+                    }
+                }
+                Some((slot, field)) => {
+                    let idx = template
+                        .content
+                        .iter()
+                        .position(|c| c.name.as_deref() == Some(slot))
+                        .or_else(|| slot.parse().ok())
+                        .filter(|&idx| idx < template.content.len());
+                    let content = match idx {
+                        Some(idx) => &mut template.content[idx],
+                        None => {
+                            checker.invalid(key, slot, "the name or index of a slot");
+                            continue;
+                        }
+                    };
+
+                    match field {
+                        "fontSize" => match value.parse() {
+                            Ok(size) => content.font_size = size,
+                            Err(_) => checker.invalid(key, value, "a number"),
+                        },
+                        "orientation" => {
+                            content.orientation = convert_orientation(&mut checker, key, value)
+                        }
+                        _ => checker.invalid("attributes", key, KEYS_ERR),
+                    }
+                }
+                None => checker.invalid("attributes", key, KEYS_ERR),
+            }
+        }
+
+        // removed at the end, so the indices always refer to the template
+        template.decorations = template
+            .decorations
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| !hidden.contains(i))
+            .map(|(_, d)| d)
+            .collect();
+
+        (template, checker.into_errors())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        ColorRef, ConfigError, ContentTemplate, Decoration, HorOrientation, Orientation, Point,
+        Rectangle, Shape, SlideTemplate, VertOrientation,
+    };
+
+    fn area() -> Rectangle<f64> {
+        Rectangle {
+            orig: Point { x: 0.0, y: 0.0 },
+            size: Point { x: 1.0, y: 1.0 },
+        }
+    }
+
+    fn template() -> SlideTemplate {
+        let decoration = Decoration {
+            area: area(),
+            shape: Shape::Rectangle,
+            fill: None,
+            stroke: None,
+            image: None,
+        };
+        let slot = |name: &str| ContentTemplate {
+            name: Some(String::from(name)),
+            area: area(),
+            font_size: 18.0,
+            orientation: Orientation::default(),
+            caption: None,
+        };
+
+        SlideTemplate {
+            decorations: vec![decoration.clone(), decoration],
+            background: None,
+            content: vec![slot("title"), slot("body")],
+            furniture: true,
+        }
+    }
+
+    fn attributes(attributes: &[(&str, &str)]) -> Vec<(String, String)> {
+        attributes
+            .iter()
+            .map(|&(k, v)| (String::from(k), String::from(v)))
+            .collect()
+    }
+
+    #[test]
+    fn overrides() {
+        let (template, errors) = template().with_overrides(&attributes(&[
+            ("background", "2"),
+            ("hide", "0"),
+            ("title.fontSize", "40"),
+            ("1.orientation", "middle right"),
+        ]));
+
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert!(matches!(template.background, Some(ColorRef::Index(2))));
+        assert_eq!(template.decorations.len(), 1);
+        assert_eq!(template.content[0].font_size, 40.0);
+        assert_eq!(
+            template.content[1].orientation,
+            Orientation {
+                vertical: VertOrientation::Middle,
+                horizontal: HorOrientation::Right,
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_overrides() {
+        let original = template();
+        let (template, errors) = original.with_overrides(&attributes(&[
+            ("hide", "0,5"),
+            ("body.fontSize", "big"),
+            ("footer.fontSize", "20"),
+            ("title.color", "1"),
+            ("size", "2"),
+        ]));
+
+        let keys: Vec<_> = errors
+            .into_iter()
+            .map(|e| match e {
+                ConfigError::Value { key, .. } => key,
+                e => panic!("expected a value error, found {}", e),
+            })
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                "hide",
+                "body.fontSize",
+                "footer.fontSize",
+                "attributes",
+                "attributes"
+            ]
+        );
+        // the valid parts are still applied
+        assert_eq!(template.decorations.len(), 1);
+        assert_eq!(template.content[1].font_size, 18.0);
+        assert_eq!(original.decorations.len(), 2);
+    }
+}
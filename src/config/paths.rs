@@ -0,0 +1,73 @@
+use std::path::{Path, PathBuf};
+
+/// finds the file at the path, relative paths are looked up in the dir of the
+/// file referencing it first and then in the search paths in their order.
+/// Returns the tried locations if the file isn't at any of them
+pub fn locate(path: &Path, dir: &Path, search: &[PathBuf]) -> Result<PathBuf, Vec<PathBuf>> {
+    let tried: Vec<_> = if path.is_absolute() {
+        vec![path.to_path_buf()]
+    } else {
+        std::iter::once(dir)
+            .chain(search.iter().map(PathBuf::as_path))
+            .map(|d| d.join(path))
+            .collect()
+    };
+
+    tried.iter().find(|p| p.exists()).cloned().ok_or(tried)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::locate;
+    use std::{fs, path::PathBuf};
+
+    /// an empty directory for the test with the name
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("slidmk-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("present")).unwrap();
+        fs::create_dir_all(dir.join("assets")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn relative_to_the_dir_first() {
+        let dir = test_dir("dir-first");
+        let (present, assets) = (dir.join("present"), dir.join("assets"));
+        fs::write(present.join("a.png"), "").unwrap();
+        fs::write(assets.join("a.png"), "").unwrap();
+        fs::write(assets.join("b.png"), "").unwrap();
+        let search = [dir.join("missing"), assets.clone()];
+
+        assert_eq!(
+            locate("a.png".as_ref(), &present, &search),
+            Ok(present.join("a.png"))
+        );
+        assert_eq!(
+            locate("b.png".as_ref(), &present, &search),
+            Ok(assets.join("b.png"))
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn missing_lists_the_tried_paths() {
+        let dir = test_dir("missing");
+        let (present, assets) = (dir.join("present"), dir.join("assets"));
+
+        assert_eq!(
+            locate("c.png".as_ref(), &present, std::slice::from_ref(&assets)),
+            Err(vec![present.join("c.png"), assets.join("c.png")])
+        );
+
+        // absolute paths are only tried as they are
+        let absolute = dir.join("c.png");
+        assert_eq!(
+            locate(&absolute, &present, &[assets]),
+            Err(vec![absolute.clone()])
+        );
+        fs::write(&absolute, "").unwrap();
+        assert_eq!(locate(&absolute, &present, &[]), Ok(absolute));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
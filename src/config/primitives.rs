@@ -9,14 +9,28 @@ pub struct Color {
     pub r: f64,
     pub g: f64,
     pub b: f64,
+    /// the opacity, 1.0 is fully opaque
+    pub a: f64,
 }
 
 impl Color {
     pub fn new(r: f64, g: f64, b: f64) -> Self {
-        Color { r, g, b }
+        Color { r, g, b, a: 1.0 }
     }
 }
 
+/// a color used by the style or templates, it's looked up in the
+/// style when it's drawn, so it follows changes of the style
+#[derive(Clone, Debug)]
+pub enum ColorRef {
+    /// the index inside the colors of the style
+    Index(usize),
+    /// the name of a named color of the style
+    Name(String),
+    /// a color written directly as hex
+    Color(Color),
+}
+
 /// a simple 2d point with both coords going from the top-left
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Deserialize)]
 pub struct Point<T> {
@@ -103,6 +117,33 @@ where
     }
 }
 
+impl Rectangle<f64> {
+    /// splits the rectangle horizontally into a top and bottom part,
+    /// the top part gets the given share of the height
+    pub fn split_vertical(&self, share: f64) -> (Self, Self) {
+        let top_height = self.size.y * share;
+        let top = Rectangle {
+            orig: self.orig,
+            size: Point {
+                x: self.size.x,
+                y: top_height,
+            },
+        };
+        let bottom = Rectangle {
+            orig: Point {
+                x: self.orig.x,
+                y: self.orig.y + top_height,
+            },
+            size: Point {
+                x: self.size.x,
+                y: self.size.y - top_height,
+            },
+        };
+
+        (top, bottom)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum VertOrientation {
     Top,
@@ -131,3 +172,33 @@ impl Default for Orientation {
         }
     }
 }
+
+/// where a caption is drawn relative to its image
+#[derive(Debug, Clone, PartialEq)]
+pub enum CaptionPosition {
+    Above,
+    Below,
+}
+
+/// how raster images are compressed inside the pdf
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageCompression {
+    /// lossless
+    Flate,
+    Jpeg,
+}
+
+impl std::str::FromStr for ImageCompression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "flate" => Ok(Self::Flate),
+            "jpeg" | "jpg" => Ok(Self::Jpeg),
+            _ => Err(format!(
+                "unknown image compression {}, expected flate or jpeg",
+                s
+            )),
+        }
+    }
+}
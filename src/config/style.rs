@@ -0,0 +1,240 @@
+use super::{
+    de_se::{Convert, StyleJson},
+    error::{Checker, ConfigError},
+    format, Point, PresentStyle,
+};
+use serde_hjson::{Map, Value};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// reads the style at the path and checks its values
+pub fn load_style(path: &Path) -> Result<PresentStyle, Vec<ConfigError>> {
+    let value = read_style(path).map_err(|e| vec![e])?;
+    convert_style(value, path)
+}
+
+/// checks the values of the style, the problems are reported for the file
+pub fn convert_style(value: Value, file: &Path) -> Result<PresentStyle, Vec<ConfigError>> {
+    let json: StyleJson = serde_hjson::from_value(value).map_err(|e| {
+        vec![ConfigError::file(
+            file,
+            format!("invalid style format due to: {}", e),
+        )]
+    })?;
+
+    let mut checker = Checker::new(Some(file));
+    let style = json.convert(&mut checker);
+    checker.finish(style)
+}
+
+/// reads the style at the path. A style can extend another one with the
+/// extends key, the path of it is relative to the style and its fields
+/// are overridden by the ones of the style, even inside of objects
+pub fn read_style(path: &Path) -> Result<Value, ConfigError> {
+    read_value(path, &mut vec![])
+}
+
+/// reads the style as value with the extended styles merged into it,
+/// the chain contains the styles which are currently read to find cycles
+fn read_value(path: &Path, chain: &mut Vec<PathBuf>) -> Result<Value, ConfigError> {
+    let source = fs::read_to_string(path).map_err(|e| ConfigError::file(path, e.to_string()))?;
+    match format::parse(path, &source)? {
+        Value::Object(map) => extend(map, path, chain),
+        _ => Err(ConfigError::file(path, "the style needs to be an object")),
+    }
+}
+
+/// the default style as value, a partial style can be merged on top of it
+pub fn default_value() -> Value {
+    let style = PresentStyle::default();
+    let object = |fields: Vec<(&str, Value)>| {
+        Value::Object(
+            fields
+                .into_iter()
+                .map(|(k, v)| (String::from(k), v))
+                .collect(),
+        )
+    };
+    let point = |p: Point<f64>| object(vec![("x", Value::F64(p.x)), ("y", Value::F64(p.y))]);
+    let hex = |c: f64| (c * 255.0).round() as u8;
+
+    object(vec![
+        (
+            "colors",
+            Value::Array(
+                style
+                    .colors
+                    .iter()
+                    .map(|c| {
+                        Value::String(format!("%{:02X}{:02X}{:02X}", hex(c.r), hex(c.g), hex(c.b)))
+                    })
+                    .collect(),
+            ),
+        ),
+        ("font", Value::String(style.font)),
+        (
+            "margin",
+            object(vec![
+                ("orig", point(style.margin.orig)),
+                ("size", point(style.margin.size)),
+            ]),
+        ),
+        ("lineSpace", Value::F64(style.line_spacing)),
+    ])
+}
+
+/// merges the style, which is inside the file, into the one it extends
+pub fn extend(
+    mut map: Map<String, Value>,
+    path: &Path,
+    chain: &mut Vec<PathBuf>,
+) -> Result<Value, ConfigError> {
+    let extends = match map.remove("extends") {
+        Some(Value::String(extends)) => extends,
+        Some(value) => {
+            return Err(ConfigError::Value {
+                file: Some(path.to_path_buf()),
+                key: String::from("extends"),
+                value: value.to_string(),
+                expected: String::from("the path of a style"),
+            })
+        }
+        None => return Ok(Value::Object(map)),
+    };
+
+    let canonical = path
+        .canonicalize()
+        .map_err(|e| ConfigError::file(path, e.to_string()))?;
+    if chain.contains(&canonical) {
+        return Err(ConfigError::file(path, "the style extends itself"));
+    }
+    chain.push(canonical);
+
+    let parent = path.parent().unwrap_or_else(|| Path::new("")).join(extends);
+    let mut style = read_value(&parent, chain)?;
+    merge(&mut style, Value::Object(map));
+
+    Ok(style)
+}
+
+/// overrides the values of the base with the ones of the other value,
+/// objects are merged field by field
+pub fn merge(base: &mut Value, other: Value) {
+    match (base, other) {
+        (Value::Object(base), Value::Object(other)) => {
+            for (key, value) in other {
+                match base.get_mut(&key) {
+                    Some(base_value) => merge(base_value, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, other) => *base = other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merge, read_style, ConfigError};
+    use serde_hjson::Value;
+    use std::{fs, path::PathBuf};
+
+    /// an empty directory for the test with the name
+    fn test_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("slidmk-style-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("present")).unwrap();
+        fs::create_dir_all(dir.join("themes")).unwrap();
+        dir
+    }
+
+    fn value(source: &str) -> Value {
+        serde_hjson::from_str(source).unwrap()
+    }
+
+    #[test]
+    fn merge_overrides_the_parent() {
+        let mut base = value(
+            r#"{
+                font: "Base"
+                colors: ["%000000", "%FFFFFF"]
+                margin: {orig: {x: 0.1, y: 0.2}}
+            }"#,
+        );
+        merge(
+            &mut base,
+            value(r#"{colors: ["%FF0000"], margin: {orig: {x: 0.3}}, lineSpace: 2}"#),
+        );
+
+        assert_eq!(base.find("font").and_then(Value::as_str), Some("Base"));
+        // lists are replaced as a whole, objects field by field
+        assert_eq!(
+            base.find("colors").and_then(Value::as_array).map(Vec::len),
+            Some(1)
+        );
+        assert_eq!(
+            base.find_path(&["margin", "orig", "x"])
+                .and_then(Value::as_f64),
+            Some(0.3)
+        );
+        assert_eq!(
+            base.find_path(&["margin", "orig", "y"])
+                .and_then(Value::as_f64),
+            Some(0.2)
+        );
+        assert_eq!(base.find("lineSpace").and_then(Value::as_u64), Some(2));
+    }
+
+    #[test]
+    fn extends_relative_to_the_style() {
+        let dir = test_dir("relative");
+        fs::write(
+            dir.join("themes/common.hjson"),
+            "{font: Common\nlineSpace: 1}",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("themes/base.hjson"),
+            "{extends: common.hjson\nfont: Base\ndimColor: 2}",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("present/style.hjson"),
+            "{extends: ../themes/base.hjson\ndimColor: 3}",
+        )
+        .unwrap();
+
+        let style = read_style(&dir.join("present/style.hjson")).unwrap();
+        assert_eq!(style.find("font").and_then(Value::as_str), Some("Base"));
+        assert_eq!(style.find("lineSpace").and_then(Value::as_u64), Some(1));
+        assert_eq!(style.find("dimColor").and_then(Value::as_u64), Some(3));
+        assert!(style.find("extends").is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn extends_cycle() {
+        let dir = test_dir("cycle");
+        fs::write(dir.join("present/a.hjson"), "{\nextends: b.hjson\n}").unwrap();
+        fs::write(dir.join("present/b.hjson"), "{\nextends: a.hjson\n}").unwrap();
+        fs::write(dir.join("present/self.hjson"), "{\nextends: self.hjson\n}").unwrap();
+
+        for file in ["a.hjson", "self.hjson"] {
+            match read_style(&dir.join("present").join(file)) {
+                Err(ConfigError::File { reason, .. }) => {
+                    assert_eq!(reason, "the style extends itself")
+                }
+                result => panic!("expected the cycle to be found, got {:?}", result),
+            }
+        }
+        // a missing parent is reported, not ignored
+        fs::write(dir.join("present/c.hjson"), "{\nextends: missing.hjson\n}").unwrap();
+        assert!(read_style(&dir.join("present/c.hjson")).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -13,6 +13,7 @@ pub enum DrawError {
     ImageNotLoaded(ImageError),
     KindNotFound(String),
     NoColor(usize),
+    NoNamedColor(String),
     Pdf(pdf::PdfError),
 }
 
@@ -25,6 +26,7 @@ impl Display for DrawError {
             IoError(e) => e.fmt(f),
             KindNotFound(actual) => write!(f, "couldn't find pdf kind {}", actual),
             NoColor(idx) => write!(f, "no color found at index {}.", idx),
+            NoNamedColor(name) => write!(f, "no color named {} found.", name),
             ImageNotLoaded(e) => write!(f, "couldn't decode the image due to: {}", e),
             Pdf(e) => write!(f, "an pdf error occurred: {}", e),
         }
@@ -12,4 +12,10 @@ type DResult<T> = Result<T, DrawError>;
 pub trait Drawer {
     fn write<W: Write>(self, to: W) -> DResult<()>;
     fn create_slide(&mut self, slides: Slide, config: &Config) -> DResult<()>;
+    /// starts a section, which groups all following slides
+    fn start_section(&mut self, name: &str);
+    /// sets the amount of slides in the whole presentation
+    fn set_total_slides(&mut self, total: usize);
+    /// sets an entry of the document metadata
+    fn set_metadata(&mut self, key: &str, value: &str) -> DResult<()>;
 }
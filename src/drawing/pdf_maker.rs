@@ -1,38 +1,155 @@
 use super::{DResult, DrawError, Drawer};
-use crate::config::{self, Config, ContentTemplate, Decoration};
-use crate::parser::{Content, Slide};
+use crate::config::{self, CaptionPosition, Config, ContentTemplate, Decoration, Fill, Furniture};
+use crate::parser::{Content, LinkTarget, ListItem, Slide, Text};
 use crate::util::pdf;
 use std::io::Write;
+use std::path::Path;
 
+/// the dpi used to convert page sizes in pixels
 const DPI: u16 = 300;
-// TODO: maybe look for the screen size
-const SIZE: pdf::Size = pdf::Size::Px(1920, 1080);
 
 pub struct PdfMaker {
     doc: pdf::Document,
+    /// the amount of slides already created
+    slide_count: usize,
+    /// the amount of slides in the whole presentation
+    total_slides: usize,
+    /// the name of the current section
+    section: Option<String>,
+}
+
+/// the build step of a slide which is currently drawn
+#[derive(Clone, Copy)]
+struct BuildStep {
+    current: usize,
+    /// the color of the text revealed in earlier steps,
+    /// if it's none the text isn't dimmed
+    dim: Option<config::Color>,
+}
+
+impl BuildStep {
+    /// if something revealed in the step is drawn
+    fn shows(&self, step: usize) -> bool {
+        step <= self.current
+    }
+
+    /// the color of the text revealed in the step,
+    /// the text visible from the start is never dimmed
+    fn color(&self, step: usize, foreground: config::Color) -> config::Color {
+        match self.dim {
+            Some(dim) if step > 0 && step < self.current => dim,
+            _ => foreground,
+        }
+    }
 }
 
 impl Drawer for PdfMaker {
     fn create_slide(&mut self, slide: Slide, config: &Config) -> DResult<()> {
         // get info of how the slide should be drawn
-        let kind = config
+        let template = config
             .slide_templates
             .get(&slide.kind)
             .ok_or_else(|| DrawError::KindNotFound(slide.kind.clone()))?;
 
-        // create the new pdf page for the slide
-        let mut page = self.doc.new_page("");
+        // the attributes of the slide change the template for it
+        let (kind, problems) = template.with_overrides(&slide.attributes);
+        let (slots, slot_problems) = Self::fill_slots(&slide, &kind.content);
 
-        let foreground = config.get_color(1)?;
+        // the pages of the slide are named after its title
+        // or the first text in the order of the slots
+        let name = Self::slide_name(&slide, &slots)
+            .map(String::from)
+            .unwrap_or_else(|| format!("Slide {}", self.slide_count + 1));
+        self.slide_count += 1;
 
-        Self::draw_decorations(&mut page, &kind.decorations, config)?;
-        Self::draw_content(
-            &mut page,
-            &kind.content,
-            slide,
-            &config.style.font,
-            foreground,
-        )
+        for e in problems.iter() {
+            eprintln!("{}: {}", name, e);
+        }
+        for problem in slot_problems.iter() {
+            eprintln!("{}: {}", name, problem);
+        }
+        let background = match kind.background.as_ref().map(|c| config.get_color(c)) {
+            Some(Err(e)) => {
+                eprintln!("{}: {}", name, e);
+                template
+                    .background
+                    .as_ref()
+                    .map(|c| config.get_color(c))
+                    .transpose()?
+            }
+            color => color.transpose()?,
+        };
+        let placeholders = self.placeholders();
+
+        let foreground = config.foreground()?;
+        let link = pdf::LinkArgs {
+            color: config
+                .style
+                .link
+                .color
+                .as_ref()
+                .map(|c| config.get_color(c))
+                .transpose()?,
+            underline: config.style.link.underline,
+        };
+        let dim = config
+            .style
+            .dim_color
+            .as_ref()
+            .map(|c| config.get_color(c))
+            .transpose()?;
+
+        // every build step gets its own page, which all belong
+        // to the same slide. Handouts only show the last step
+        let steps = slide.step_count();
+        let first = if config.handout.is_some() {
+            steps - 1
+        } else {
+            0
+        };
+
+        for current in first..steps {
+            let mut page = self.doc.new_page(name.as_str());
+            if current == first {
+                page.add_bookmark(&name);
+                if let Some(label) = &slide.label {
+                    page.add_label(label);
+                }
+            }
+
+            if let Some(color) = background {
+                page.draw_background(color);
+            }
+            Self::draw_decorations(&mut page, &kind.decorations, config)?;
+            Self::draw_content(
+                &mut page,
+                &slots,
+                &slide,
+                BuildStep { current, dim },
+                &config.style.font,
+                foreground,
+                link,
+            )?;
+
+            if kind.furniture {
+                Self::draw_furniture(&mut page, &config.style.furniture, &placeholders, config)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn start_section(&mut self, name: &str) {
+        self.doc.start_section(name);
+        self.section = Some(String::from(name));
+    }
+
+    fn set_total_slides(&mut self, total: usize) {
+        self.total_slides = total;
+    }
+
+    fn set_metadata(&mut self, key: &str, value: &str) -> DResult<()> {
+        self.doc.set_metadata(key, value).map_err(|e| e.into())
     }
 
     /// writes the document to the file system
@@ -45,8 +162,25 @@ impl PdfMaker {
     /// creates a pdf maker with information from the
     /// config
     pub fn with_config(config: &Config) -> DResult<Self> {
-        let doc = pdf::Document::new(config.doc_name, SIZE, config.style.margin.clone(), DPI)?;
-        let drawer = Self { doc };
+        let mut doc = pdf::Document::new(
+            config.doc_name,
+            config.style.page_size,
+            config.style.margin.clone(),
+            DPI,
+            config.style.image.clone(),
+        )?;
+        for dir in config.font_dirs.iter() {
+            doc.add_font_dir(dir);
+        }
+        if let Some(handout) = config.handout {
+            doc.use_handout(handout);
+        }
+        let drawer = Self {
+            doc,
+            slide_count: 0,
+            total_slides: 0,
+            section: None,
+        };
 
         Ok(drawer)
     }
@@ -59,52 +193,283 @@ impl PdfMaker {
     ) -> DResult<()> {
         for d in decos.iter() {
             let area = page.doc.scale_pdf_rect(d.area.clone());
-            let color = config.get_color(d.color_idx)?;
+            let fill = match &d.fill {
+                Some(Fill::Color(color)) => Some(config.get_color(color)?),
+                Some(Fill::Linear { from, to, angle }) => {
+                    let gradient = pdf::Gradient::Linear {
+                        from: config.get_color(from)?,
+                        to: config.get_color(to)?,
+                        angle: *angle,
+                    };
+                    page.draw_gradient(&area, &d.shape, &gradient);
+                    None
+                }
+                Some(Fill::Radial { from, to }) => {
+                    let gradient = pdf::Gradient::Radial {
+                        from: config.get_color(from)?,
+                        to: config.get_color(to)?,
+                    };
+                    page.draw_gradient(&area, &d.shape, &gradient);
+                    None
+                }
+                None => None,
+            };
+            let stroke = match &d.stroke {
+                Some(s) => Some(pdf::Stroke {
+                    color: config.get_color(&s.color)?,
+                    width: s.width,
+                    dash: &s.dash,
+                }),
+                None => None,
+            };
+
+            page.draw_shape(&area, &d.shape, fill, stroke.as_ref());
+            if let Some(image) = &d.image {
+                // logos and watermarks are never cropped
+                page.draw_image(image, &area, pdf::Fit::Contain, None)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// the values of the placeholders inside
+    /// the furniture for the current slide
+    fn placeholders(&self) -> [(&'static str, String); 5] {
+        let metadata = self.doc.metadata();
+
+        [
+            ("{slide}", self.slide_count.to_string()),
+            ("{total}", self.total_slides.to_string()),
+            ("{title}", metadata.title.clone().unwrap_or_default()),
+            ("{date}", metadata.date().format("%F")),
+            ("{section}", self.section.clone().unwrap_or_default()),
+        ]
+    }
+
+    /// replaces the placeholders inside the text with their values,
+    /// unknown ones are kept as they are
+    fn expand_placeholders(text: &str, placeholders: &[(&str, String)]) -> String {
+        placeholders
+            .iter()
+            .fold(String::from(text), |text, (key, value)| {
+                text.replace(key, value)
+            })
+    }
 
-            page.draw_rect(&area, None, Some(color))
+    /// draws the furniture with its placeholders replaced
+    fn draw_furniture(
+        page: &mut pdf::Page,
+        furniture: &[Furniture],
+        placeholders: &[(&str, String)],
+        config: &Config,
+    ) -> DResult<()> {
+        for f in furniture.iter() {
+            let text = Self::expand_placeholders(&f.text, placeholders);
+
+            let args = pdf::TextArgs {
+                area: page.doc.scale_pdf_rect(f.area.clone()),
+                font_size: f.font_size as f64,
+                font: &config.style.font,
+                orientation: &f.orientation,
+                foreground: Some(match &f.color {
+                    Some(color) => config.get_color(color)?,
+                    None => config.foreground()?,
+                }),
+                link: pdf::LinkArgs {
+                    color: None,
+                    underline: false,
+                },
+            };
+            page.draw_text(&args, &text)?;
         }
 
         Ok(())
     }
 
-    /// draws the content of a slide to the pdf page
+    /// the explicit title of the slide or the first text in the filled slots,
+    /// so the first slot of the template names it and not the first content
+    fn slide_name<'s>(slide: &'s Slide, slots: &[(&ContentTemplate, usize)]) -> Option<&'s str> {
+        slide.title.as_deref().or_else(|| {
+            slots.iter().find_map(|&(_, i)| match &slide.contents[i] {
+                Content::Text(t) => Some(t.text.as_str()),
+                _ => None,
+            })
+        })
+    }
+
+    /// matches the contents of the slide to the slots of the template, named
+    /// contents go into the slot with their name and the others fill the free
+    /// slots in order. Returns the filled slots with the index of their content
+    /// and the problems, like contents which are dropped
+    fn fill_slots<'t>(
+        slide: &Slide,
+        slots: &'t [ContentTemplate],
+    ) -> (Vec<(&'t ContentTemplate, usize)>, Vec<String>) {
+        let mut filled = vec![None; slots.len()];
+        let mut unnamed = vec![];
+        let mut problems = vec![];
+
+        for (i, slot) in slide.slots.iter().enumerate() {
+            let slot = match slot {
+                Some(slot) => slot,
+                None => {
+                    unnamed.push(i);
+                    continue;
+                }
+            };
+
+            match slots.iter().position(|s| s.name.as_ref() == Some(slot)) {
+                Some(idx) if filled[idx].is_none() => filled[idx] = Some(i),
+                Some(_) => problems.push(format!(
+                    "the slot {} is filled twice, dropping the content",
+                    slot
+                )),
+                None => problems.push(format!(
+                    "the kind {} has no slot named {}, dropping the content",
+                    slide.kind, slot
+                )),
+            }
+        }
+
+        let mut free = filled.iter_mut().filter(|s| s.is_none());
+        let surplus = unnamed
+            .into_iter()
+            .filter(|&i| match free.next() {
+                Some(slot) => {
+                    *slot = Some(i);
+                    false
+                }
+                None => true,
+            })
+            .count();
+
+        if surplus > 0 {
+            problems.push(format!(
+                "{} contents don't fit into the kind {}, dropping them",
+                surplus, slide.kind
+            ));
+        }
+        for (template, _) in slots.iter().zip(filled.iter()).filter(|(_, c)| c.is_none()) {
+            problems.push(match &template.name {
+                Some(slot) => format!("the slot {} isn't filled", slot),
+                None => format!("a slot of the kind {} isn't filled", slide.kind),
+            });
+        }
+
+        let filled = slots
+            .iter()
+            .zip(filled)
+            .filter_map(|(template, content)| Some((template, content?)))
+            .collect();
+        (filled, problems)
+    }
+
+    /// draws the content of a slide revealed
+    /// until the build step to the pdf page
     fn draw_content(
         page: &mut pdf::Page,
-        contents: &[ContentTemplate],
-        slide: Slide,
+        slots: &[(&ContentTemplate, usize)],
+        slide: &Slide,
+        build: BuildStep,
         font: &str,
         foreground: config::Color,
+        link: pdf::LinkArgs,
     ) -> DResult<()> {
-        for (template, content) in contents.iter().zip(slide.contents.into_iter()) {
+        for &(template, i) in slots {
+            let (content, step) = (&slide.contents[i], slide.steps[i]);
+            if !build.shows(step) {
+                continue;
+            }
+
             let area = page.doc.scale_pdf_rect(template.area.clone());
             let args = pdf::TextArgs {
                 area,
                 font_size: template.font_size as f64,
                 font,
                 orientation: &template.orientation,
-                // TODO: add the colors
-                foreground: Some(foreground),
+                foreground: Some(build.color(step, foreground)),
+                link,
             };
 
             match content {
-                Content::Text(s) => {
-                    page.draw_text(&args, &s)?;
+                Content::Text(t) => {
+                    page.draw_linked_text(&args, &t.text, &Self::links(t))?;
                 }
                 Content::Config(_) => panic!("Config calls should be handled before drawing"),
-                Content::Image(_, p) => {
-                    // TODO: add description
-                    page.draw_image(p, &args.area)?;
-                }
-                Content::List(i) => Self::list(page, i, args)?,
+                Content::Image(desc, p) => Self::image(page, template, desc, p, args)?,
+                Content::List(i) => Self::list(page, i, build, foreground, args)?,
             }
         }
 
         Ok(())
     }
 
+    /// draws the image with its description as caption,
+    /// if the template has a caption
+    fn image<'a>(
+        page: &mut pdf::Page,
+        template: &'a ContentTemplate,
+        desc: &str,
+        path: &Path,
+        mut args: pdf::TextArgs<'a>,
+    ) -> DResult<()> {
+        let alt = Some(desc).filter(|d| !d.trim().is_empty());
+
+        let caption = match (&template.caption, alt) {
+            (Some(caption), Some(_)) => caption,
+            _ => {
+                return page
+                    .draw_image(path, &args.area, pdf::Fit::Cover, alt)
+                    .map_err(|e| e.into())
+            }
+        };
+
+        let (image_area, caption_area) = match caption.position {
+            CaptionPosition::Above => {
+                let (caption, image) = template.area.split_vertical(caption.size);
+                (image, caption)
+            }
+            CaptionPosition::Below => {
+                let (image, caption) = template.area.split_vertical(1.0 - caption.size);
+                (image, caption)
+            }
+        };
+
+        page.draw_image(
+            path,
+            &page.doc.scale_pdf_rect(image_area),
+            pdf::Fit::Cover,
+            alt,
+        )?;
+
+        args.area = page.doc.scale_pdf_rect(caption_area);
+        args.font_size = caption.font_size as f64;
+        args.orientation = &caption.orientation;
+        page.draw_text(&args, desc)?;
+
+        Ok(())
+    }
+
+    /// the links of the text for drawing it
+    fn links(text: &Text) -> Vec<pdf::Link<'_>> {
+        text.links
+            .iter()
+            .map(|l| pdf::Link {
+                range: l.range.clone(),
+                target: match &l.target {
+                    LinkTarget::Uri(uri) => pdf::LinkTarget::Uri(uri),
+                    LinkTarget::Label(label) => pdf::LinkTarget::Label(label),
+                },
+            })
+            .collect()
+    }
+
     fn list(
         page: &mut pdf::Page,
-        items: Vec<(u8, String)>,
+        items: &[ListItem],
+        build: BuildStep,
+        foreground: config::Color,
         mut args: pdf::TextArgs,
     ) -> DResult<()> {
         use printpdf::Pt;
@@ -115,12 +480,16 @@ impl PdfMaker {
         if args.orientation != &Default::default() {
             eprintln!("warning list are currently only supported in top-left orientation");
         }
-        for (ident, text) in items {
+        // the items are in the order of their steps,
+        // so the hidden ones are at the end
+        for item in items.iter().take_while(|i| build.shows(i.step)) {
+            let ListItem { indent, text, step } = item;
+            args.foreground = Some(build.color(*step, foreground));
             page.new_layer("please end my suffering");
             // the ident of the list item and drawing the symbol
             let mut ident_pos = orig
                 + config::Point {
-                    x: ident_width * ident as f64,
+                    x: ident_width * *indent as f64,
                     y: Pt(0.0),
                 };
             page.doc.set_lower_left(&mut args.area, ident_pos);
@@ -131,7 +500,7 @@ impl PdfMaker {
             // TODO: move the area to the right according to the ident
 
             // writing the item and move down to the next item
-            let pt_written = page.draw_text(&args, &text)?;
+            let pt_written = page.draw_linked_text(&args, &text.text, &Self::links(text))?;
             // decrease the height of the area
             page.doc
                 .move_upper_right(&mut args.area, (Pt(0.0), Pt(0.0) - pt_written).into());
@@ -140,3 +509,81 @@ impl PdfMaker {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::PdfMaker;
+    use crate::config::Config;
+    use crate::drawing::Drawer;
+    use crate::parser::{self, Slide};
+
+    fn slide(source: &str) -> Slide {
+        parser::parse(source).next().unwrap()
+    }
+
+    /// the name of the slide with the slots of the default Head_Cont
+    fn name(slide: &Slide) -> (Option<String>, Vec<String>) {
+        let config = Config::default();
+        let template = &config.slide_templates["Head_Cont"];
+        let (slots, problems) = PdfMaker::fill_slots(slide, &template.content);
+        (
+            PdfMaker::slide_name(slide, &slots).map(String::from),
+            problems,
+        )
+    }
+
+    #[test]
+    fn named_after_the_first_slot() {
+        let in_order = slide("--- Head_Cont\n\nTitle\n\nBody\n");
+        assert_eq!(name(&in_order), (Some(String::from("Title")), vec![]));
+
+        // the title slot comes first in the template, not in the source
+        let reversed = slide("--- Head_Cont\n\n@body Body\n\n@title Title\n");
+        assert_eq!(name(&reversed), (Some(String::from("Title")), vec![]));
+
+        let unnamed = slide("--- Head_Cont\n\n@body Body\n\nTitle\n");
+        assert_eq!(name(&unnamed), (Some(String::from("Title")), vec![]));
+
+        let titled = slide("--- Head_Cont Explicit\n\n@body Body\n\n@title Title\n");
+        assert_eq!(name(&titled).0.as_deref(), Some("Explicit"));
+    }
+
+    #[test]
+    fn placeholders() {
+        let mut maker = PdfMaker::with_config(&Config::default()).unwrap();
+        maker.set_total_slides(12);
+        maker.set_metadata("title", "SlidMk").unwrap();
+        maker.set_metadata("date", "2021-11-19").unwrap();
+        let expand =
+            |maker: &PdfMaker, text| PdfMaker::expand_placeholders(text, &maker.placeholders());
+
+        // before the first section and slide
+        assert_eq!(expand(&maker, "{section}|{slide}"), "|0");
+
+        maker.start_section("Intro");
+        maker.slide_count = 3;
+        assert_eq!(
+            expand(&maker, "{slide} / {total} {title} {date} {section}"),
+            "3 / 12 SlidMk 2021-11-19 Intro"
+        );
+        assert_eq!(
+            expand(&maker, "{slide}{slide} {page} {Title} {"),
+            "33 {page} {Title} {"
+        );
+    }
+
+    #[test]
+    fn slot_problems() {
+        let (_, problems) = name(&slide(
+            "--- Head_Cont\n\n@body A\n\n@body B\n\n@nothing C\n",
+        ));
+        assert_eq!(
+            problems,
+            vec![
+                "the slot body is filled twice, dropping the content",
+                "the kind Head_Cont has no slot named nothing, dropping the content",
+                "the slot title isn't filled",
+            ]
+        );
+    }
+}
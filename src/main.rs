@@ -1,10 +1,12 @@
 #![feature(result_flattening, try_blocks, iter_intersperse)]
+use std::collections::HashSet;
 use std::fs::File;
+use std::path::{Path, PathBuf};
 
 use crate::{
-    config::Config,
+    config::{locate, Config, ConfigError, FrontMatter},
     drawing::{pdf_maker::PdfMaker, DrawError, Drawer},
-    parser::Content,
+    parser::{Content, Slide},
 };
 
 mod cli_args;
@@ -16,16 +18,53 @@ mod util;
 fn main() -> Result<(), DrawError> {
     let args = cli_args::get();
 
+    let source = std::fs::read_to_string(&args.present_file).unwrap();
+    let (front_matter, source) = match parser::split_front_matter(&source) {
+        Ok(split) => split,
+        Err(e) => {
+            eprintln!("{}: {}", args.present_file.display(), e);
+            std::process::exit(1);
+        }
+    };
+    let front_matter = front_matter.map(|f| FrontMatter {
+        file: args.present_file.clone(),
+        format: f.format.map(String::from),
+        source: String::from(f.source),
+    });
+
     let mut config = Config::builder()
         .with_style(args.style)
         .with_templates(args.templates)
+        .with_image_options(args.image)
+        .with_handout(args.handout)
+        .with_font_dirs(args.font_dirs)
+        .with_search_paths(args.search_paths)
+        .with_front_matter(front_matter)
         .build(&args.doc_name);
 
-    let source = std::fs::read_to_string(args.present_file).unwrap();
-    let slides = parser::parse(&source);
-    let mut pdf = PdfMaker::with_config(&config).expect("couldn't get the pdfmaker");
+    let mut slides = parser::parse(source).collect::<Vec<_>>();
+    // only the templates used by the slides are checked
+    let kinds: HashSet<String> = slides.iter().map(|s| s.kind.clone()).collect();
+    check_colors(&config, &kinds);
+    let missing = locate_files(&mut slides, &args.present_file, &config.search_paths);
+    if !missing.is_empty() {
+        exit_with(&missing);
+    }
+    let mut pdf = match PdfMaker::with_config(&config) {
+        Ok(pdf) => pdf,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    pdf.set_total_slides(
+        slides
+            .iter()
+            .filter(|s| !matches!(s.kind.as_str(), "Style" | "Section" | "Metadata"))
+            .count(),
+    );
 
-    for slide in slides {
+    for (i, slide) in slides.into_iter().enumerate() {
         match slide.kind.as_str() {
             "Style" => {
                 let path = slide
@@ -39,9 +78,23 @@ fn main() -> Result<(), DrawError> {
                     .flatten()
                     .expect("expected path to a style sheet");
 
-                config
-                    .change_style(path)
-                    .expect("Couldn't load style sheet");
+                if let Err(errors) = config.change_style(path) {
+                    exit_with(&errors);
+                }
+                check_colors(&config, &kinds);
+            }
+            "Section" => match slide.name() {
+                Some(name) => pdf.start_section(name),
+                None => exit_at(
+                    &args.present_file,
+                    i,
+                    "a section needs a title like --- Section Intro",
+                ),
+            },
+            "Metadata" => {
+                if let Err(e) = set_metadata(&mut pdf, &slide) {
+                    exit_at(&args.present_file, i, &e);
+                }
             }
             _ => pdf
                 .create_slide(slide, &config)
@@ -52,3 +105,80 @@ fn main() -> Result<(), DrawError> {
     let file = File::create(args.output).expect("couldn't open file");
     pdf.write(file)
 }
+
+/// sets the metadata from the list items of the slide like * title: SlidMk,
+/// the problem contains the entry which is invalid
+fn set_metadata(pdf: &mut PdfMaker, slide: &Slide) -> Result<(), String> {
+    const EXPECTED: &str = "expected the metadata as list items like * title: SlidMk";
+
+    for content in slide.contents.iter() {
+        let items = match content {
+            Content::List(items) => items,
+            Content::Text(t) => return Err(format!("{}, found {}", EXPECTED, t.text)),
+            _ => return Err(String::from(EXPECTED)),
+        };
+
+        for item in items.iter() {
+            let line = item.text.text.as_str();
+            let (key, value) = line
+                .split_once(':')
+                .ok_or_else(|| format!("expected an entry like key: value, found {}", line))?;
+
+            pdf.set_metadata(key.trim(), value.trim())
+                .map_err(|e| format!("{}: {}", line, e))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// stops if a color used by the style or the templates of the kinds doesn't exist
+fn check_colors(config: &Config, kinds: &HashSet<String>) {
+    let errors = config.check_colors(kinds);
+    if !errors.is_empty() {
+        exit_with(&errors);
+    }
+}
+
+/// finds the images and styles of the slides, they're relative to the
+/// presentation or inside the search paths. Returns the missing ones
+fn locate_files(slides: &mut [Slide], present: &Path, search: &[PathBuf]) -> Vec<ConfigError> {
+    let dir = present.parent().unwrap_or_else(|| Path::new(""));
+    let mut missing = vec![];
+
+    for slide in slides.iter_mut() {
+        let name = String::from(slide.name().unwrap_or(&slide.kind));
+        for content in slide.contents.iter_mut() {
+            let path = match content {
+                Content::Image(_, path) | Content::Config(path) => path,
+                _ => continue,
+            };
+
+            match locate(path, dir, search) {
+                Ok(found) => *path = found,
+                Err(tried) => missing.push(ConfigError::Missing {
+                    file: Some(present.to_path_buf()),
+                    key: name.clone(),
+                    path: path.clone(),
+                    tried,
+                }),
+            }
+        }
+    }
+
+    missing
+}
+
+/// prints the problem of the slide at the index and stops
+fn exit_at(present: &Path, index: usize, message: &str) -> ! {
+    eprintln!("{}: slide {}: {}", present.display(), index + 1, message);
+    std::process::exit(1);
+}
+
+/// prints the problems of the config and stops
+fn exit_with(errors: &[ConfigError]) -> ! {
+    for e in errors.iter() {
+        eprintln!("{}", e);
+    }
+    std::process::exit(1);
+}
@@ -1,4 +1,4 @@
-use super::Token;
+use super::{tokens::Place, Token};
 use regex::Regex;
 use std::iter::Iterator;
 
@@ -8,11 +8,16 @@ pub type TokenCreator = dyn for<'s> Fn(usize, regex::Captures<'s>) -> Token<'s>
 /// from a given char-iterator
 pub struct Lexer<'a, 's> {
     pub no_captures: &'a [(Regex, Token<'s>)],
-    pub captures: &'a [(Regex, &'a TokenCreator)],
+    /// the tokens with the place they can be found
+    pub captures: &'a [(Regex, Place, &'a TokenCreator)],
     pub comment: &'a Regex,
     pub whitespace: &'a Regex,
     pub invalid: Token<'s>,
     pub source: &'s str,
+    /// if the next token is at the start of a content
+    pub content_start: bool,
+    /// if the current content is a list
+    pub in_list: bool,
 }
 
 impl<'a, 's> Iterator for Lexer<'a, 's> {
@@ -41,19 +46,31 @@ impl<'a, 's> Iterator for Lexer<'a, 's> {
             }
         }
 
+        let tok = self.find_token(indent);
+        self.update_place(&tok);
+        Some(tok)
+    }
+}
+
+impl<'a, 's> Lexer<'a, 's> {
+    fn find_token(&mut self, indent: usize) -> Token<'s> {
         // look for a simple token like a linefeed ('\n')
         for (re, tok) in self.no_captures.iter() {
             match re.find(self.source) {
                 Some(m) if m.start() == 0 => {
                     let tok = tok.clone();
                     self.update_pos(m.end());
-                    return Some(tok);
+                    return tok;
                 }
                 _ => (),
             }
         }
 
-        for (re, tok_fn) in self.captures.iter() {
+        for (re, place, tok_fn) in self.captures.iter() {
+            if !self.allows(*place) {
+                continue;
+            }
+
             if let Some(c) = re.captures(self.source) {
                 let full = c.get(0).unwrap();
 
@@ -63,16 +80,39 @@ impl<'a, 's> Iterator for Lexer<'a, 's> {
 
                 let tok = tok_fn(indent, c);
                 self.update_pos(full.end());
-                return Some(tok);
+                return tok;
             }
         }
 
         self.source = "";
-        Some(self.invalid.clone())
+        self.invalid.clone()
+    }
+
+    fn allows(&self, place: Place) -> bool {
+        match place {
+            Place::Anywhere => true,
+            Place::ContentStart => self.content_start,
+            Place::ListLine => self.content_start || self.in_list,
+        }
+    }
+
+    /// keeps track of the start of contents, the markers of a content
+    /// like > and @slot are only found before its text
+    fn update_place(&mut self, tok: &Token<'s>) {
+        match tok {
+            Token::Linefeed => {
+                self.content_start = true;
+                self.in_list = false;
+            }
+            Token::Step | Token::Slot(_) => (),
+            Token::ListPre { .. } => {
+                self.content_start = false;
+                self.in_list = true;
+            }
+            _ => self.content_start = false,
+        }
     }
-}
 
-impl<'a, 's> Lexer<'a, 's> {
     fn update_pos(&mut self, pos: usize) {
         self.source = &self.source[pos..];
     }
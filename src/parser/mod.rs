@@ -7,18 +7,77 @@ mod slide;
 mod slides;
 mod tokens;
 
+pub use parse_error::ParseError;
 pub use slide::*;
 
+/// the config at the start of the source between two lines of +++,
+/// the format of it can follow the first one like +++ yaml
+#[derive(Debug)]
+pub struct FrontMatter<'a> {
+    pub format: Option<&'a str>,
+    pub source: &'a str,
+}
+
+/// the front matter and the slides of the source
+pub type Split<'a> = (Option<FrontMatter<'a>>, &'a str);
+
+/// splits the front matter from the slides, it's an error
+/// if the front matter doesn't end
+pub fn split_front_matter(source: &str) -> Result<Split<'_>, ParseError<'static>> {
+    let start = source.trim_start();
+    let (first, body) = start.split_once('\n').unwrap_or((start, ""));
+    let format = match first.trim().strip_prefix("+++") {
+        Some(format) => format.trim(),
+        None => return Ok((None, source)),
+    };
+
+    // it ends at the next line of +++
+    let mut len = 0;
+    for line in body.split_inclusive('\n') {
+        if line.trim() == "+++" {
+            let front_matter = FrontMatter {
+                format: Some(format).filter(|f| !f.is_empty()),
+                source: &body[..len],
+            };
+            return Ok((Some(front_matter), &body[len + line.len()..]));
+        }
+        len += line.len();
+    }
+
+    let line = source[..source.len() - start.len()].matches('\n').count() + 1;
+    Err(ParseError {
+        expected: "+++ at the end of the front matter",
+        actual: format!(
+            "the end of the file, the front matter starts at line {}",
+            line
+        ),
+    })
+}
+
 /// takes a reference to some source and returns the lazily
 /// parsed slides
 pub fn parse(source: &'_ str) -> impl Iterator<Item = Slide> + '_ {
-    let tokens = lexer::Lexer {
+    slides::lazy_parser(lex(source)).map(|s| match s {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("{:?}", e);
+            std::process::exit(1);
+        }
+    })
+    //.inspect(|s| println!("slide: {:?}", s))
+}
+
+/// splits the source into its tokens, without repeated linefeeds
+fn lex(source: &str) -> Vec<Token<'_>> {
+    lexer::Lexer {
         source,
         no_captures: tokens::NON_CAPTURES.as_ref(),
         captures: tokens::CAPTURES.as_ref(),
         comment: &tokens::COMMENT,
         whitespace: &tokens::WHITESPACE,
         invalid: Token::Illegal,
+        content_start: true,
+        in_list: false,
     }
     // remove repeated linefeeds
     .filter({
@@ -31,14 +90,98 @@ pub fn parse(source: &'_ str) -> impl Iterator<Item = Slide> + '_ {
         }
     })
     //.inspect(|t| eprintln!("{:?}", t))
-    .collect();
+    .collect()
+}
 
-    slides::lazy_parser(tokens).map(|s| match s {
-        Ok(s) => s,
-        Err(e) => {
-            eprintln!("{:?}", e);
-            std::process::exit(1);
-        }
-    })
-    //.inspect(|s| println!("slide: {:?}", s))
+#[cfg(test)]
+mod tests {
+    use super::{lex, split_front_matter, Token};
+
+    #[test]
+    fn without_front_matter() {
+        let source = "--- Title\n\na\n";
+        let (front_matter, rest) = split_front_matter(source).unwrap();
+        assert!(front_matter.is_none());
+        assert_eq!(rest, source);
+    }
+
+    #[test]
+    fn front_matter() {
+        let (front_matter, rest) =
+            split_front_matter("\n+++ yaml\nstyle: {}\n +++ \n--- Title\n").unwrap();
+        let front_matter = front_matter.unwrap();
+        assert_eq!(front_matter.format, Some("yaml"));
+        assert_eq!(front_matter.source, "style: {}\n");
+        assert_eq!(rest, "--- Title\n");
+
+        let (front_matter, _) = split_front_matter("+++\n+++\n").unwrap();
+        assert_eq!(front_matter.unwrap().format, None);
+    }
+
+    #[test]
+    fn unclosed_front_matter() {
+        let error = split_front_matter("\n\n+++\nstyle: {}\n--- Title\n").unwrap_err();
+        assert!(error.actual.ends_with("line 3"), "{}", error.actual);
+    }
+
+    #[test]
+    fn step_only_at_content_start() {
+        assert_eq!(
+            lex("> a\n> 5 users\n"),
+            vec![Token::Step, Token::Text("a"), Token::Text("> 5 users")]
+        );
+    }
+
+    #[test]
+    fn slot_only_at_content_start() {
+        assert_eq!(
+            lex("@title > a\nat @home\n"),
+            vec![
+                Token::Slot("title"),
+                Token::Step,
+                Token::Text("a"),
+                Token::Text("at @home")
+            ]
+        );
+        assert_eq!(
+            lex("a\n@home\n"),
+            vec![Token::Text("a"), Token::Text("@home")]
+        );
+    }
+
+    #[test]
+    fn list_marker_only_in_lists() {
+        assert_eq!(
+            lex("a\n+1 for this\n"),
+            vec![Token::Text("a"), Token::Text("+1 for this")]
+        );
+        assert_eq!(
+            lex("* a\n  b\n+ c\n"),
+            vec![
+                Token::ListPre {
+                    indent: 0,
+                    step: false
+                },
+                Token::Text("a"),
+                Token::Text("b"),
+                Token::ListPre {
+                    indent: 0,
+                    step: true
+                },
+                Token::Text("c"),
+            ]
+        );
+    }
+
+    #[test]
+    fn escaped_marker() {
+        assert_eq!(
+            lex("\\> 5 users\n\n\\- 3 degrees\n"),
+            vec![
+                Token::Text("> 5 users"),
+                Token::Linefeed,
+                Token::Text("- 3 degrees")
+            ]
+        );
+    }
 }
@@ -1,15 +1,197 @@
+use std::ops::Range;
 use std::path::PathBuf;
 
 #[derive(Debug)]
 pub struct Slide {
     pub kind: String,
+    /// the label other slides can link to
+    pub label: Option<String>,
+    /// the title written behind the kind
+    pub title: Option<String>,
     pub contents: Vec<Content>,
+    /// the build step every content is revealed in,
+    /// the slide starts at step 0
+    pub steps: Vec<usize>,
+    /// the name of the template slot for every content,
+    /// without one it fills the next free slot
+    pub slots: Vec<Option<String>>,
+    /// the overrides of the template for this slide only as key and value,
+    /// written inside of braces after the header like {background=2}
+    pub attributes: Vec<(String, String)>,
+}
+
+impl Slide {
+    /// the name of the slide, it's the explicit title
+    /// or the first text in the source of the slide
+    pub fn name(&self) -> Option<&str> {
+        self.title.as_deref().or_else(|| {
+            self.contents.iter().find_map(|c| match c {
+                Content::Text(t) => Some(t.text.as_str()),
+                _ => None,
+            })
+        })
+    }
+
+    /// the amount of build steps, every step is drawn as its own page
+    pub fn step_count(&self) -> usize {
+        let last = self
+            .contents
+            .iter()
+            .zip(self.steps.iter())
+            .map(|(content, &step)| match content {
+                Content::List(items) => items.last().map_or(step, |i| i.step),
+                _ => step,
+            })
+            .max();
+
+        last.unwrap_or(0) + 1
+    }
 }
 
 #[derive(Debug)]
 pub enum Content {
-    Text(String),
+    Text(Text),
     Config(PathBuf),
     Image(String, PathBuf),
-    List(Vec<(u8, String)>),
+    List(Vec<ListItem>),
+}
+
+#[derive(Debug)]
+pub struct ListItem {
+    pub indent: u8,
+    pub text: Text,
+    /// the build step the item is revealed in
+    pub step: usize,
+}
+
+/// text with the links inside of it
+#[derive(Debug, Default)]
+pub struct Text {
+    pub text: String,
+    pub links: Vec<Link>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Link {
+    /// the part of the text which is linked
+    pub range: Range<usize>,
+    pub target: LinkTarget,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum LinkTarget {
+    Uri(String),
+    /// the label of another slide
+    Label(String),
+}
+
+impl Text {
+    /// parses the links in the format of {text}(target) out of the text,
+    /// a target starting with # is the label of a slide. Parentheses inside
+    /// the target need to be balanced like in (https://w.org/a_(b))
+    pub fn parse(source: &str) -> Self {
+        let mut parsed = Self::default();
+        let mut rest = source;
+
+        while let Some(start) = rest.find('{') {
+            let link = rest[start + 1..].split_once('}').and_then(|(text, after)| {
+                let after = after.strip_prefix('(')?;
+                Some((text, &after[..closing_paren(after)?]))
+            });
+
+            let (text, target) = match link {
+                Some(link) => link,
+                // no link, so it's normal text
+                None => {
+                    parsed.text.push_str(&rest[..=start]);
+                    rest = &rest[start + 1..];
+                    continue;
+                }
+            };
+
+            parsed.text.push_str(&rest[..start]);
+            let range_start = parsed.text.len();
+            parsed.text.push_str(text);
+
+            parsed.links.push(Link {
+                range: range_start..parsed.text.len(),
+                target: match target.strip_prefix('#') {
+                    Some(label) => LinkTarget::Label(String::from(label)),
+                    None => LinkTarget::Uri(String::from(target)),
+                },
+            });
+
+            // {text}(target)
+            rest = &rest[start + text.len() + target.len() + 4..];
+        }
+
+        parsed.text.push_str(rest);
+        parsed
+    }
+}
+
+/// the index of the ) closing the one which is already open
+fn closing_paren(source: &str) -> Option<usize> {
+    let mut depth = 0;
+
+    for (i, c) in source.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(i),
+            ')' => depth -= 1,
+            _ => (),
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Link, LinkTarget, Text};
+
+    fn uri(range: std::ops::Range<usize>, uri: &str) -> Link {
+        Link {
+            range,
+            target: LinkTarget::Uri(String::from(uri)),
+        }
+    }
+
+    #[test]
+    fn text_without_links() {
+        let text = Text::parse("a {b} (c) d");
+        assert_eq!(text.text, "a {b} (c) d");
+        assert!(text.links.is_empty());
+    }
+
+    #[test]
+    fn text_with_links() {
+        let text = Text::parse("see {the docs}(https://a.org) and {why}(#why)");
+        assert_eq!(text.text, "see the docs and why");
+        assert_eq!(
+            text.links,
+            vec![
+                uri(4..12, "https://a.org"),
+                Link {
+                    range: 17..20,
+                    target: LinkTarget::Label(String::from("why")),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn link_target_with_parentheses() {
+        let target = "https://en.wikipedia.org/wiki/Rust_(programming_language)";
+        let text = Text::parse(&format!("{{Rust}}({}) is fast", target));
+        assert_eq!(text.text, "Rust is fast");
+        assert_eq!(text.links, vec![uri(0..4, target)]);
+    }
+
+    #[test]
+    fn unclosed_link_target() {
+        let text = Text::parse("{a}(b (c) d");
+        assert_eq!(text.text, "{a}(b (c) d");
+        assert!(text.links.is_empty());
+    }
 }
@@ -2,7 +2,7 @@ use super::{
     combinators,
     combinators::Parser,
     parse_error::ParseError,
-    slide::{Content, Slide},
+    slide::{Content, ListItem, Slide, Text},
     tokens::Token,
 };
 
@@ -28,10 +28,16 @@ macro_rules! token_fn {
     };
 }
 
-token_fn!(identifier, &'s str, Token::Identifier(t) => t);
+token_fn!(
+    identifier,
+    (&'s str, Option<&'s str>, Option<&'s str>, Option<&'s str>),
+    Token::Identifier { kind, label, title, attributes } => (*kind, *label, *title, *attributes)
+);
 token_fn!(text, &'s str, Token::Text(t) => t);
 token_fn!(path, &'s Path, Token::Path(p) => p);
-token_fn!(list_pre, u8, Token::ListPre(i) => *i);
+token_fn!(list_pre, (u8, bool), Token::ListPre { indent, step } => (*indent, *step));
+token_fn!(step, (), Token::Step => ());
+token_fn!(slot, &'s str, Token::Slot(s) => s);
 token_fn!(right_bracket, (), Token::SqrBracketRight => ());
 token_fn!(left_bracket, (), Token::SqrBracketLeft => ());
 token_fn!(line_feed, (), Token::Linefeed => ());
@@ -40,7 +46,18 @@ fn construct_slide_parser<'s>() -> impl Parser<Token<'s>, Output = Slide> {
     let text = text
         .many()
         .process(|v| v.into_iter().intersperse(" ").collect());
-    let list = list_pre.and(text.clone()).many().process(Content::List);
+    let linked_text = text.clone().process(|t: String| Text::parse(&t));
+    // the step of an item is only 1 if it starts a new step,
+    // the actual step is counted when the whole slide is parsed
+    let list = list_pre
+        .and(linked_text.clone())
+        .process(|((indent, step), text)| ListItem {
+            indent,
+            text,
+            step: step as usize,
+        })
+        .many()
+        .process(Content::List);
 
     // TODO: fix problem where you can't write ] in normal text
     let image = text
@@ -50,23 +67,105 @@ fn construct_slide_parser<'s>() -> impl Parser<Token<'s>, Output = Slide> {
         .and(path)
         .process(|(desc, path)| Content::Image(desc, path.into()));
 
-    let content = path
-        .process(|p| Content::Config(p.into()))
-        .or(image)
-        .or(list)
-        .or(text.process(Content::Text))
+    let no_slot = |_: &[Token<'s>], offset| combinators::p_ok(offset, None);
+    let no_step = |_: &[Token<'s>], offset| combinators::p_ok(offset, false);
+    let content = slot
+        .process(Some)
+        .or(no_slot)
+        .and(
+            step.process(|_| true).or(no_step).and(
+                path.process(|p| Content::Config(p.into()))
+                    .or(image)
+                    .or(list)
+                    .or(linked_text.process(Content::Text)),
+            ),
+        )
         .suffix(line_feed.or(combinators::eof));
     //.inspect(|c| eprintln!("found Content: {:?}", c));
 
+    // slides without content are fine, ex. for sections
+    let no_content = |_: &[Token<'s>], offset| combinators::p_ok(offset, vec![]);
+
     identifier
         .suffix(line_feed)
-        .and(content.many())
-        .process(|(kind, content)| Slide {
-            kind: kind.into(),
-            contents: content,
+        .and(content.many().or(no_content))
+        .process(|((kind, label, title, attrs), content)| {
+            let (slots, content): (Vec<_>, _) = content
+                .into_iter()
+                .map(|(slot, content)| (slot.map(String::from), content))
+                .unzip();
+            let (contents, steps) = count_steps(content);
+
+            Slide {
+                kind: kind.into(),
+                label: label.map(String::from),
+                title: title.map(String::from),
+                contents,
+                steps,
+                slots,
+                attributes: attrs.map(attributes).unwrap_or_default(),
+            }
         })
 }
 
+/// splits the attributes in the format of key=value, values
+/// with spaces are quoted like orientation="top left"
+fn attributes(source: &str) -> Vec<(String, String)> {
+    let mut attributes = vec![];
+    let mut rest = source.trim_start();
+
+    while !rest.is_empty() {
+        let key_end = rest
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let key = &rest[..key_end];
+        rest = &rest[key_end..];
+
+        let value = match rest.strip_prefix('=') {
+            Some(quoted) if quoted.starts_with('"') => {
+                let end = quoted[1..].find('"').map_or(quoted.len(), |e| e + 1);
+                rest = quoted.get(end + 1..).unwrap_or("");
+                &quoted[1..end]
+            }
+            Some(value) => {
+                let end = value.find(char::is_whitespace).unwrap_or(value.len());
+                rest = &value[end..];
+                &value[..end]
+            }
+            // an attribute without value
+            None => "",
+        };
+
+        attributes.push((String::from(key), String::from(value)));
+        rest = rest.trim_start();
+    }
+
+    attributes
+}
+
+/// numbers the build steps of the contents and list items
+/// in the order they appear inside the slide
+fn count_steps(content: Vec<(bool, Content)>) -> (Vec<Content>, Vec<usize>) {
+    let mut step = 0;
+
+    content
+        .into_iter()
+        .map(|(new_step, mut content)| {
+            step += new_step as usize;
+            let content_step = step;
+
+            if let Content::List(items) = &mut content {
+                for item in items.iter_mut() {
+                    step += item.step;
+                    item.step = step;
+                }
+            }
+
+            (content, content_step)
+        })
+        .unzip()
+}
+
 pub fn lazy_parser(
     tokens: Vec<Token<'_>>,
 ) -> impl Iterator<Item = Result<Slide, ParseError<'static>>> + '_ {
@@ -88,3 +187,62 @@ pub fn lazy_parser(
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::slide::{Content, ListItem, Text};
+
+    fn item(step: bool) -> ListItem {
+        ListItem {
+            indent: 0,
+            text: Text::default(),
+            step: step as usize,
+        }
+    }
+
+    fn pairs(attributes: &[(&str, &str)]) -> Vec<(String, String)> {
+        attributes
+            .iter()
+            .map(|&(k, v)| (String::from(k), String::from(v)))
+            .collect()
+    }
+
+    #[test]
+    fn attributes() {
+        assert_eq!(
+            super::attributes(" background=2  hide=0,1 title.orientation=\"top left\" flag"),
+            pairs(&[
+                ("background", "2"),
+                ("hide", "0,1"),
+                ("title.orientation", "top left"),
+                ("flag", ""),
+            ])
+        );
+        assert_eq!(super::attributes(""), vec![]);
+        // an unclosed quote goes until the end
+        assert_eq!(super::attributes("a=\"b c"), pairs(&[("a", "b c")]));
+    }
+
+    #[test]
+    fn count_steps() {
+        let content = vec![
+            (false, Content::Text(Text::default())),
+            (
+                true,
+                Content::List(vec![item(false), item(true), item(true)]),
+            ),
+            (false, Content::Text(Text::default())),
+            (true, Content::Text(Text::default())),
+        ];
+        let (contents, steps) = super::count_steps(content);
+
+        assert_eq!(steps, vec![0, 1, 3, 4]);
+        match &contents[1] {
+            Content::List(items) => {
+                let item_steps: Vec<_> = items.iter().map(|i| i.step).collect();
+                assert_eq!(item_steps, vec![1, 2, 3]);
+            }
+            c => panic!("expected a list, found {:?}", c),
+        }
+    }
+}
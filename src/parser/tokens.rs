@@ -10,8 +10,34 @@ pub enum Token<'a> {
     SqrBracketRight,
     Path(&'a Path),
     Text(&'a str),
-    Identifier(&'a str),
-    ListPre(u8),
+    /// the kind of a slide with an optional label, title
+    /// and the attributes inside of braces at the end
+    Identifier {
+        kind: &'a str,
+        label: Option<&'a str>,
+        title: Option<&'a str>,
+        attributes: Option<&'a str>,
+    },
+    /// the start of a list item, a step item
+    /// is revealed in its own build step
+    ListPre {
+        indent: u8,
+        step: bool,
+    },
+    /// reveals the following content in its own build step
+    Step,
+    /// the name of the template slot for the following content
+    Slot(&'a str),
+}
+
+/// where a token can be found
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Place {
+    Anywhere,
+    /// only at the start of a content, before its text
+    ContentStart,
+    /// at the start of a content or of a line inside a list
+    ListLine,
 }
 
 fn regex(re: &str) -> Regex {
@@ -28,11 +54,15 @@ lazy_static::lazy_static! {
         (regex("\n"), Token::Linefeed),
     ];
 
-    pub static ref CAPTURES: [(Regex, &'static lexer::TokenCreator); 4] = [
-        (regex(r"---\s*([^\s\d]+)"), &identifier),
-        (regex(r"-|\*"), &list_item),
-        (regex(r#""(.*)""#), &path),
-        (regex(r"([^\]\n]*)\n?"), &text),
+    pub static ref CAPTURES: [(Regex, Place, &'static lexer::TokenCreator); 7] = [
+        // a \ at the start of a line keeps the rest as text, ex. \> 5 users
+        (regex(r"\\([^\]\n]*)\n?"), Place::Anywhere, &text),
+        (regex(r"---\s*([^\s\d]+)[^\S\n]*(?:#(\S+))?[^\S\n]*([^\n{]*)(?:\{([^}\n]*)\}[^\S\n]*)?"), Place::Anywhere, &identifier),
+        (regex(">"), Place::ContentStart, &step),
+        (regex(r"@(\w+)"), Place::ContentStart, &slot),
+        (regex(r"-|\*|\+"), Place::ListLine, &list_item),
+        (regex(r#""(.*)""#), Place::Anywhere, &path),
+        (regex(r"([^\]\n]*)\n?"), Place::Anywhere, &text),
     ];
 }
 
@@ -44,10 +74,28 @@ fn path(_: usize, capture: Captures) -> Token {
     Token::Path(capture.get(1).unwrap().as_str().as_ref())
 }
 
+fn step(_: usize, _: Captures) -> Token {
+    Token::Step
+}
+
+fn slot(_: usize, capture: Captures) -> Token {
+    Token::Slot(capture.get(1).unwrap().as_str())
+}
+
 fn identifier(_: usize, capture: Captures) -> Token {
-    Token::Identifier(capture.get(1).unwrap().as_str())
+    let title = capture.get(3).map(|t| t.as_str().trim_end());
+
+    Token::Identifier {
+        kind: capture.get(1).unwrap().as_str(),
+        label: capture.get(2).map(|l| l.as_str()),
+        title: title.filter(|t| !t.is_empty()),
+        attributes: capture.get(4).map(|a| a.as_str()),
+    }
 }
 
-fn list_item(ident: usize, _: Captures) -> Token {
-    Token::ListPre(ident as u8)
+fn list_item(ident: usize, capture: Captures) -> Token {
+    Token::ListPre {
+        indent: ident as u8,
+        step: capture.get(0).unwrap().as_str() == "+",
+    }
 }
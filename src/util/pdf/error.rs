@@ -8,6 +8,9 @@ pub enum PdfError {
     File(io::Error),
     Image(image::ImageError),
     Pdf(printpdf::Error),
+    PostProcess(lopdf::Error),
+    Svg(usvg::Error),
+    Metadata(String),
 }
 
 impl std::error::Error for PdfError {}
@@ -22,6 +25,9 @@ impl fmt::Display for PdfError {
             File(e) => write!(f, "Couldn't read file due to {}", e),
             Image(e) => write!(f, "Couldn't load image due to {}", e),
             Pdf(e) => write!(f, "An pdf error occured: {}", e),
+            PostProcess(e) => write!(f, "Couldn't finish the pdf due to {}", e),
+            Svg(e) => write!(f, "Couldn't load svg due to {}", e),
+            Metadata(e) => write!(f, "Invalid metadata: {}", e),
         }
     }
 }
@@ -43,3 +49,15 @@ impl From<printpdf::Error> for PdfError {
         Self::Pdf(e)
     }
 }
+
+impl From<lopdf::Error> for PdfError {
+    fn from(e: lopdf::Error) -> Self {
+        Self::PostProcess(e)
+    }
+}
+
+impl From<usvg::Error> for PdfError {
+    fn from(e: usvg::Error) -> Self {
+        Self::Svg(e)
+    }
+}
@@ -0,0 +1,271 @@
+use super::PdfRect;
+use crate::config;
+use lopdf::content::Operation;
+use printpdf::{Mm, Pt};
+
+/// the size of an a4 page in portrait
+pub const PAGE_SIZE: (Mm, Mm) = (Mm(210.0), Mm(297.0));
+/// the space around the slides and between them in pt
+const MARGIN: f64 = 28.0;
+const GAP: f64 = 14.0;
+/// the space between two lines for notes in pt
+const LINE_SPACE: f64 = 22.0;
+
+/// places the slides onto the pages of a handout
+pub struct Handout {
+    /// the places for the slides on every page
+    slots: Vec<Slot>,
+    /// the index of the next slot, 0 starts a new page
+    next: usize,
+    /// the page which is currently filled
+    pub page: Option<printpdf::indices::PdfPageIndex>,
+}
+
+/// the place of a slide on a handout page
+pub struct Slot {
+    pub transform: Transform,
+    /// the area of the slide on the handout page
+    pub area: PdfRect,
+    /// the lines for notes next to the slide
+    pub notes: Vec<(printpdf::Point, printpdf::Point)>,
+}
+
+/// maps the coordinates of a slide to its place on a handout page
+#[derive(Clone, Copy)]
+pub struct Transform {
+    scale: f64,
+    offset: (f64, f64),
+}
+
+impl Handout {
+    /// lays out the slides of the given size on the page, the slides are in
+    /// one column with note lines next to them or in two columns without
+    pub fn new(handout: config::Handout, slide_size: (Pt, Pt)) -> Self {
+        let page_size: (Pt, Pt) = (PAGE_SIZE.0.into(), PAGE_SIZE.1.into());
+        let count = handout.slides_per_page as usize;
+        let columns = if handout.notes || count <= 2 { 1 } else { 2 };
+        let rows = count.div_ceil(columns);
+
+        let cell = (
+            (page_size.0 .0 - 2.0 * MARGIN - GAP * (columns - 1) as f64) / columns as f64,
+            (page_size.1 .0 - 2.0 * MARGIN - GAP * (rows - 1) as f64) / rows as f64,
+        );
+        // the notes take the right half of the cell
+        let slide_cell = if handout.notes {
+            ((cell.0 - GAP) / 2.0, cell.1)
+        } else {
+            cell
+        };
+        let scale = (slide_cell.0 / slide_size.0 .0).min(slide_cell.1 / slide_size.1 .0);
+        let slide = (slide_size.0 .0 * scale, slide_size.1 .0 * scale);
+
+        let slots = (0..count)
+            .map(|i| {
+                // the first slot is at the top left
+                let cell_orig = (
+                    MARGIN + (i % columns) as f64 * (cell.0 + GAP),
+                    page_size.1 .0 - MARGIN - cell.1 - (i / columns) as f64 * (cell.1 + GAP),
+                );
+                let orig = (
+                    cell_orig.0 + (slide_cell.0 - slide.0) / 2.0,
+                    cell_orig.1 + (slide_cell.1 - slide.1) / 2.0,
+                );
+
+                let notes = if handout.notes {
+                    let left = cell_orig.0 + slide_cell.0 + GAP;
+                    let right = cell_orig.0 + cell.0;
+                    let point = |x, y| printpdf::Point { x: Pt(x), y: Pt(y) };
+
+                    (1..)
+                        .map(|line| orig.1 + slide.1 - line as f64 * LINE_SPACE)
+                        .take_while(|&y| y >= orig.1)
+                        .map(|y| (point(left, y), point(right, y)))
+                        .collect()
+                } else {
+                    vec![]
+                };
+
+                Slot {
+                    transform: Transform {
+                        scale,
+                        offset: orig,
+                    },
+                    area: PdfRect(config::Rectangle {
+                        orig: (Pt(orig.0), Pt(orig.1)).into(),
+                        size: (Pt(slide.0), Pt(slide.1)).into(),
+                    }),
+                    notes,
+                }
+            })
+            .collect();
+
+        Self {
+            slots,
+            next: 0,
+            page: None,
+        }
+    }
+
+    /// the slot for the next slide, if it's the
+    /// first slot a new page needs to be added
+    pub fn next_slot(&mut self) -> &Slot {
+        let slot = self.next;
+        self.next = (slot + 1) % self.slots.len();
+        &self.slots[slot]
+    }
+
+    /// if the next slide is drawn onto a new page
+    pub fn starts_page(&self) -> bool {
+        self.next == 0
+    }
+}
+
+impl Transform {
+    /// maps the rectangle from the slide to the handout page
+    pub fn apply(&self, rect: &PdfRect) -> PdfRect {
+        let config::Rectangle { orig, size } = rect.0;
+
+        PdfRect(config::Rectangle {
+            orig: (
+                Pt(orig.x.0 * self.scale + self.offset.0),
+                Pt(orig.y.0 * self.scale + self.offset.1),
+            )
+                .into(),
+            size: (Pt(size.x.0 * self.scale), Pt(size.y.0 * self.scale)).into(),
+        })
+    }
+
+    /// the operations which clip to the area of the slide and
+    /// transform everything drawn after them until they're restored
+    pub fn operations(&self, area: &PdfRect) -> Vec<Operation> {
+        let config::Rectangle { orig, size } = area.0;
+
+        vec![
+            Operation::new("q", vec![]),
+            Operation::new(
+                "re",
+                vec![
+                    orig.x.0.into(),
+                    orig.y.0.into(),
+                    size.x.0.into(),
+                    size.y.0.into(),
+                ],
+            ),
+            Operation::new("W", vec![]),
+            Operation::new("n", vec![]),
+            Operation::new(
+                "cm",
+                vec![
+                    self.scale.into(),
+                    0.into(),
+                    0.into(),
+                    self.scale.into(),
+                    self.offset.0.into(),
+                    self.offset.1.into(),
+                ],
+            ),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Handout, Slot, GAP, LINE_SPACE, MARGIN, PAGE_SIZE};
+    use crate::config;
+    use printpdf::Pt;
+
+    /// the slots of a handout for slides in 16:9
+    fn slots(slides_per_page: u8, notes: bool) -> Vec<Slot> {
+        let handout = config::Handout {
+            slides_per_page,
+            notes,
+        };
+        Handout::new(handout, (Pt(960.0), Pt(540.0))).slots
+    }
+
+    /// left, bottom, right and top of the slide on the page
+    fn bounds(slot: &Slot) -> (f64, f64, f64, f64) {
+        let config::Rectangle { orig, size } = slot.area.0;
+        (orig.x.0, orig.y.0, orig.x.0 + size.x.0, orig.y.0 + size.y.0)
+    }
+
+    /// the slides keep their ratio, stay inside the margins and don't overlap
+    fn check_layout(slots: &[Slot]) {
+        let page: (Pt, Pt) = (PAGE_SIZE.0.into(), PAGE_SIZE.1.into());
+        let within = |v: f64, max: f64| v >= MARGIN - 0.001 && v <= max - MARGIN + 0.001;
+
+        for (i, slot) in slots.iter().enumerate() {
+            let (left, bottom, right, top) = bounds(slot);
+            assert!(((right - left) / (top - bottom) - 16.0 / 9.0).abs() < 0.001);
+            assert!(within(left, page.0 .0) && within(right, page.0 .0));
+            assert!(within(bottom, page.1 .0) && within(top, page.1 .0));
+
+            for other in slots[i + 1..].iter() {
+                let (o_left, o_bottom, o_right, o_top) = bounds(other);
+                assert!(right <= o_left || o_right <= left || top <= o_bottom || o_top <= bottom);
+            }
+            for (start, end) in slot.notes.iter() {
+                assert!(within(end.x.0, page.0 .0));
+                assert!(start.y.0 >= bottom && start.y.0 <= top);
+            }
+        }
+    }
+
+    #[test]
+    fn slides_per_page() {
+        for &count in [1, 2, 4, 6].iter() {
+            for &notes in [false, true].iter() {
+                let slots = slots(count, notes);
+                assert_eq!(slots.len(), count as usize);
+                check_layout(&slots);
+                assert_eq!(slots.iter().all(|s| s.notes.is_empty()), !notes);
+            }
+        }
+    }
+
+    #[test]
+    fn columns_and_order() {
+        // without notes, more than 2 slides are in two columns from the top left
+        let two_columns = slots(4, false);
+        let (left, bottom, _, _) = bounds(&two_columns[0]);
+        let (right_left, right_bottom, _, _) = bounds(&two_columns[1]);
+        assert!(right_left > left);
+        assert_eq!(right_bottom, bottom);
+        assert!(bounds(&two_columns[2]).1 < bottom);
+
+        // with notes every slide has its own row and the lines are on the right
+        let with_notes = slots(4, true);
+        for (i, slot) in with_notes.iter().enumerate() {
+            let (left, bottom, right, top) = bounds(slot);
+            assert_eq!(left, bounds(&with_notes[0]).0);
+            if i > 0 {
+                assert!(top <= bounds(&with_notes[i - 1]).1 - GAP + 0.001);
+            }
+
+            let (start, end) = slot.notes[0];
+            assert_eq!(start.x.0, right + GAP);
+            assert!(end.x.0 > start.x.0);
+            assert_eq!(start.y.0, top - LINE_SPACE);
+            let last = slot.notes.last().unwrap().0.y.0;
+            assert!(last >= bottom && last - LINE_SPACE < bottom);
+        }
+    }
+
+    #[test]
+    fn slots_repeat_on_every_page() {
+        let mut handout = Handout::new(
+            config::Handout {
+                slides_per_page: 2,
+                notes: false,
+            },
+            (Pt(960.0), Pt(540.0)),
+        );
+
+        assert!(handout.starts_page());
+        let first = bounds(handout.next_slot());
+        assert!(!handout.starts_page());
+        handout.next_slot();
+        assert!(handout.starts_page());
+        assert_eq!(bounds(handout.next_slot()), first);
+    }
+}
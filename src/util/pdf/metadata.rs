@@ -0,0 +1,327 @@
+use super::{post, PdfError, Result};
+use lopdf::{Object, Stream};
+use printpdf::OffsetDateTime;
+
+/// information about the document, it's written into
+/// the info dictionary and the xmp metadata
+#[derive(Default)]
+pub struct Metadata {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub subject: Option<String>,
+    /// separated by commas
+    pub keywords: Option<String>,
+    pub language: Option<String>,
+    /// the creation date, if it's none the current time is used
+    pub date: Option<OffsetDateTime>,
+}
+
+impl Metadata {
+    /// sets the entry of the key, dates are given
+    /// as YYYY-MM-DD with an optional time behind it
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let text = Some(String::from(value));
+
+        match key.to_lowercase().as_str() {
+            "title" => self.title = text,
+            "author" => self.author = text,
+            "subject" => self.subject = text,
+            "keywords" => self.keywords = text,
+            "language" | "lang" => self.language = text,
+            "date" => self.date = Some(parse_date(value)?),
+            _ => return Err(PdfError::Metadata(format!("unknown key {}", key))),
+        }
+
+        Ok(())
+    }
+
+    /// the creation date of the document
+    pub fn date(&self) -> OffsetDateTime {
+        self.date.unwrap_or_else(OffsetDateTime::now_utc)
+    }
+
+    /// writes the metadata into the document
+    pub fn apply(&self, doc: &mut lopdf::Document) -> Result<()> {
+        let date = self.date();
+        let offset = date.format("%z");
+        let pdf_date = format!(
+            "{}{}'{}'",
+            date.format("D:%Y%m%d%H%M%S"),
+            &offset[..3],
+            &offset[3..]
+        );
+
+        let info = doc.trailer.get(b"Info")?.as_reference()?;
+        let info = doc.get_object_mut(info)?.as_dict_mut()?;
+        let entries = [
+            ("Title", &self.title),
+            ("Author", &self.author),
+            ("Subject", &self.subject),
+            ("Keywords", &self.keywords),
+        ];
+        for (key, value) in entries.iter() {
+            if let Some(value) = value {
+                info.set(*key, post::text_string(value));
+            }
+        }
+        info.set("Creator", Object::string_literal("slidmk"));
+        info.set("CreationDate", Object::string_literal(pdf_date.as_str()));
+        info.set("ModDate", Object::string_literal(pdf_date));
+        // printpdf claims pdf/x-3, but the transparency of decorations isn't allowed in it
+        info.remove(b"GTS_PDFXVersion");
+
+        let xmp = self.xmp(&format!(
+            "{}{}:{}",
+            date.format("%Y-%m-%dT%H:%M:%S"),
+            &offset[..3],
+            &offset[3..]
+        ));
+        // the output intent of pdf/x is removed with its color profile
+        let profiles: Vec<_> = match post::catalog_mut(doc)?.remove(b"OutputIntents") {
+            Some(Object::Array(intents)) => intents
+                .iter()
+                .filter_map(|i| i.as_dict().ok()?.get(b"DestinationOutputProfile").ok())
+                .filter_map(|p| p.as_reference().ok())
+                .collect(),
+            _ => vec![],
+        };
+        for id in profiles {
+            doc.objects.remove(&id);
+        }
+
+        let catalog = post::catalog_mut(doc)?;
+        if let Some(language) = &self.language {
+            catalog.set("Lang", post::text_string(language));
+        }
+
+        match catalog.get(b"Metadata").and_then(Object::as_reference) {
+            Ok(id) => doc.get_object_mut(id)?.as_stream_mut()?.set_content(xmp),
+            Err(_) => {
+                let stream = Stream::new(
+                    lopdf::dictionary! { "Type" => "Metadata", "Subtype" => "XML" },
+                    xmp,
+                )
+                .with_compression(false);
+                let id = doc.add_object(stream);
+                post::catalog_mut(doc)?.set("Metadata", id);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// creates the xmp packet with all the metadata
+    fn xmp(&self, date: &str) -> Vec<u8> {
+        let mut entries = vec![
+            format!("<xmp:CreateDate>{}</xmp:CreateDate>", date),
+            format!("<xmp:ModifyDate>{}</xmp:ModifyDate>", date),
+            format!("<xmp:MetadataDate>{}</xmp:MetadataDate>", date),
+            String::from("<xmp:CreatorTool>slidmk</xmp:CreatorTool>"),
+            String::from("<dc:format>application/pdf</dc:format>"),
+            String::from("<pdf:Trapped>False</pdf:Trapped>"),
+        ];
+
+        let alt = |tag: &str, text: &str| {
+            format!(
+                "<dc:{0}><rdf:Alt><rdf:li xml:lang=\"x-default\">{1}</rdf:li></rdf:Alt></dc:{0}>",
+                tag,
+                escape(text)
+            )
+        };
+        let list = |tag: &str, kind: &str, items: &mut dyn Iterator<Item = &str>| {
+            let items: String = items
+                .map(|i| format!("<rdf:li>{}</rdf:li>", escape(i.trim())))
+                .collect();
+            format!("<dc:{0}><rdf:{1}>{2}</rdf:{1}></dc:{0}>", tag, kind, items)
+        };
+
+        if let Some(title) = &self.title {
+            entries.push(alt("title", title));
+        }
+        if let Some(author) = &self.author {
+            entries.push(list(
+                "creator",
+                "Seq",
+                &mut std::iter::once(author.as_str()),
+            ));
+        }
+        if let Some(subject) = &self.subject {
+            entries.push(alt("description", subject));
+        }
+        if let Some(keywords) = &self.keywords {
+            entries.push(format!("<pdf:Keywords>{}</pdf:Keywords>", escape(keywords)));
+            entries.push(list("subject", "Bag", &mut keywords.split(',')));
+        }
+        if let Some(language) = &self.language {
+            entries.push(list(
+                "language",
+                "Bag",
+                &mut std::iter::once(language.as_str()),
+            ));
+        }
+
+        let entries: String = entries.iter().map(|e| format!("      {}\n", e)).collect();
+        format!(
+            concat!(
+                "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n",
+                "<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n",
+                "  <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n",
+                "    <rdf:Description rdf:about=\"\"\n",
+                "        xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\"\n",
+                "        xmlns:dc=\"http://purl.org/dc/elements/1.1/\"\n",
+                "        xmlns:pdf=\"http://ns.adobe.com/pdf/1.3/\">\n",
+                "{}",
+                "    </rdf:Description>\n",
+                "  </rdf:RDF>\n",
+                "</x:xmpmeta>\n",
+                "<?xpacket end=\"w\"?>"
+            ),
+            entries
+        )
+        .into_bytes()
+    }
+}
+
+/// reads the SOURCE_DATE_EPOCH environment variable, which
+/// replaces the current time to get reproducible documents
+pub fn source_date() -> Result<Option<OffsetDateTime>> {
+    let epoch = match std::env::var("SOURCE_DATE_EPOCH") {
+        Ok(epoch) => epoch,
+        Err(_) => return Ok(None),
+    };
+
+    epoch
+        .trim()
+        .parse()
+        .map(|seconds| Some(OffsetDateTime::from_unix_timestamp(seconds)))
+        .map_err(|e| {
+            PdfError::Metadata(format!(
+                "invalid SOURCE_DATE_EPOCH {}, expected the seconds since 1970: {}",
+                epoch, e
+            ))
+        })
+}
+
+/// parses a date in the format of YYYY-MM-DD, optionally followed
+/// by the time THH:MM:SS and an offset +HHMM, without one it's in UTC
+fn parse_date(date: &str) -> Result<OffsetDateTime> {
+    let full = match date.len() {
+        10 => format!("{}T00:00:00+0000", date),
+        19 => format!("{}+0000", date),
+        _ => String::from(date),
+    };
+
+    OffsetDateTime::parse(&full, "%FT%T%z").map_err(|e| {
+        PdfError::Metadata(format!(
+            "invalid date {}, expected YYYY-MM-DD with an optional time THH:MM:SS+HHMM: {}",
+            date, e
+        ))
+    })
+}
+
+/// escapes the characters with a special meaning in xml
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_date, Metadata};
+    use printpdf::{Mm, OffsetDateTime};
+
+    #[test]
+    fn dates() {
+        let timestamp = |date| parse_date(date).map(|d| d.unix_timestamp()).ok();
+
+        assert_eq!(timestamp("2021-11-19"), Some(1637280000));
+        assert_eq!(timestamp("2021-11-19T12:30:15"), Some(1637325015));
+        assert_eq!(timestamp("2021-11-19T12:30:15+0100"), Some(1637321415));
+        assert_eq!(timestamp("1970-01-01"), Some(0));
+    }
+
+    #[test]
+    fn invalid_dates() {
+        for date in [
+            "",
+            "19.11.2021",
+            "2021-13-01",
+            "2021-02-30",
+            "2021-11-19 12:30:15",
+            "2021-11-19T25:00:00",
+            "2021-11-19T12:30",
+        ] {
+            assert!(parse_date(date).is_err(), "{} is accepted", date);
+        }
+
+        let error = parse_date("tomorrow").unwrap_err().to_string();
+        assert!(error.contains("invalid date tomorrow"), "{}", error);
+    }
+
+    #[test]
+    fn set_entries() {
+        let mut metadata = Metadata::default();
+        metadata.set("title", "SlidMk").unwrap();
+        metadata.set("Author", "wetlo").unwrap();
+        metadata.set("lang", "en").unwrap();
+        metadata.set("keywords", "slides, pdf").unwrap();
+        metadata.set("date", "1970-01-02").unwrap();
+
+        assert_eq!(metadata.title.as_deref(), Some("SlidMk"));
+        assert_eq!(metadata.author.as_deref(), Some("wetlo"));
+        assert_eq!(metadata.language.as_deref(), Some("en"));
+        assert_eq!(metadata.keywords.as_deref(), Some("slides, pdf"));
+        assert_eq!(metadata.date().unix_timestamp(), 86400);
+    }
+
+    #[test]
+    fn invalid_entries() {
+        let mut metadata = Metadata::default();
+
+        let error = metadata.set("titel", "SlidMk").unwrap_err().to_string();
+        assert!(error.contains("unknown key titel"), "{}", error);
+        assert!(metadata.title.is_none());
+
+        // a bad date keeps the one before
+        metadata.set("date", "1970-01-02").unwrap();
+        assert!(metadata.set("date", "2021-02-30").is_err());
+        assert_eq!(metadata.date().unix_timestamp(), 86400);
+    }
+
+    /// a document with one page written by printpdf and read again
+    fn printpdf_document() -> lopdf::Document {
+        let (doc, _, _) = printpdf::PdfDocument::new("test", Mm(100.0), Mm(100.0), "layer");
+        let mut written = vec![];
+        doc.save(&mut std::io::BufWriter::new(&mut written))
+            .unwrap();
+        lopdf::Document::load_mem(&written).unwrap()
+    }
+
+    #[test]
+    fn no_pdfx_claim() {
+        let mut doc = printpdf_document();
+        let metadata = Metadata {
+            date: Some(OffsetDateTime::from_unix_timestamp(0)),
+            ..Default::default()
+        };
+        metadata.apply(&mut doc).unwrap();
+
+        let info = doc.trailer.get(b"Info").unwrap().as_reference().unwrap();
+        let info = doc.get_object(info).unwrap().as_dict().unwrap();
+        assert!(info.get(b"GTS_PDFXVersion").is_err());
+
+        let catalog = doc.catalog().unwrap();
+        assert!(catalog.get(b"OutputIntents").is_err());
+        let xmp = catalog.get(b"Metadata").unwrap().as_reference().unwrap();
+        let xmp = doc.get_object(xmp).unwrap().as_stream().unwrap();
+        assert!(!String::from_utf8_lossy(&xmp.content).contains("PDFX"));
+        // the color profile of the output intent isn't left behind
+        assert!(!doc
+            .objects
+            .values()
+            .filter_map(|o| o.as_stream().ok())
+            .any(|s| s.dict.has(b"N")));
+    }
+}
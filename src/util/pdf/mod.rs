@@ -3,12 +3,21 @@ use arrayvec::ArrayVec;
 use printpdf::{image, Mm, Pt};
 use std::collections::HashMap;
 use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use util::{LineData, PositionArgs, RtFont};
 
 mod error;
+mod handout;
+mod metadata;
+mod post;
+mod raster;
+mod shape;
+mod svg;
 mod util;
 
 pub use error::PdfError;
+pub use metadata::Metadata;
 pub use util::Size;
 
 /// a rectangle inside the pdf document
@@ -69,6 +78,75 @@ pub struct TextArgs<'a> {
     pub font: &'a str,
     pub orientation: &'a config::Orientation,
     pub foreground: Option<config::Color>,
+    pub link: LinkArgs,
+}
+
+/// a link over a part of a text
+pub struct Link<'a> {
+    /// the byte range of the linked text
+    pub range: Range<usize>,
+    pub target: LinkTarget<'a>,
+}
+
+pub enum LinkTarget<'a> {
+    Uri(&'a str),
+    /// the label of a page
+    Label(&'a str),
+}
+
+/// how the links inside a text are drawn
+#[derive(Clone, Copy)]
+pub struct LinkArgs {
+    /// if it's none the foreground is used
+    pub color: Option<config::Color>,
+    pub underline: bool,
+}
+
+/// how a raster image is scaled into its area, it's always centered
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Fit {
+    /// the image fills the whole area, the parts outside of it are clipped
+    Cover,
+    /// the whole image is inside the area, like svg images
+    Contain,
+}
+
+impl Fit {
+    /// the factor to scale an image with the size in pixels into the area
+    fn scale(self, (width, height): (u32, u32), area: config::Point<Pt>) -> f64 {
+        let x = area.x.0 / width as f64;
+        let y = area.y.0 / height as f64;
+        match self {
+            Self::Cover => x.max(y),
+            Self::Contain => x.min(y),
+        }
+    }
+}
+
+/// how the outline of a shape is drawn
+pub struct Stroke<'a> {
+    pub color: config::Color,
+    /// the width in pt
+    pub width: f64,
+    /// alternating lengths of dashes and gaps in pt,
+    /// up to 3 pairs, a solid line if it's empty
+    pub dash: &'a [i64],
+}
+
+/// a fill which blends between two colors, the opacity
+/// of the whole gradient is the one of the first color
+pub enum Gradient {
+    /// along the angle in degrees, 0 goes from left to right
+    Linear {
+        from: config::Color,
+        to: config::Color,
+        angle: f64,
+    },
+    /// from the center to the edges of the area
+    Radial {
+        from: config::Color,
+        to: config::Color,
+    },
 }
 
 /// the pdf document itself
@@ -86,8 +164,25 @@ pub struct Document {
     /// the printpdf document
     inner_doc: printpdf::PdfDocumentReference,
     size: (Mm, Mm),
-    dpi: u16,
+    /// how raster images are embedded
+    image_style: config::ImageStyle,
     drawing_area: PdfRect,
+    /// changes which are applied after printpdf wrote the document
+    post: post::PostProcess,
+    metadata: metadata::Metadata,
+    /// if the document is saved without anything random
+    /// in it, set by the SOURCE_DATE_EPOCH environment variable
+    reproducible: bool,
+    /// the amount of pages inside the document
+    page_count: usize,
+    /// where the slides are placed, if they're drawn onto handout pages
+    handout: Option<handout::Handout>,
+    /// the already embedded images, keyed by their path
+    /// and their size in pixels
+    images: HashMap<(PathBuf, u32, u32), post::SharedResource>,
+    /// the graphic states for the opacity, keyed by the page
+    /// and the fill and stroke opacity in thousandths
+    graphic_states: HashMap<(usize, u16, u16), String>,
 }
 
 // redefine for easier use in this module
@@ -100,7 +195,10 @@ impl Document {
         size: Size,
         drawing_area: config::Rectangle<f64>,
         dpi: u16,
+        image_style: config::ImageStyle,
     ) -> Result<Self> {
+        let name = name.into();
+        let source_date = metadata::source_date()?;
         let size = size.to_mm(dpi);
         let pt_size = (size.0.into(), size.1.into());
         let mut font_config = fontdb::Database::new();
@@ -113,30 +211,64 @@ impl Document {
             pdf_fonts: vec![],
             rt_fonts: vec![],
             font_config,
+            metadata: metadata::Metadata {
+                title: Some(name.clone()),
+                date: source_date,
+                ..Default::default()
+            },
+            reproducible: source_date.is_some(),
             inner_doc: printpdf::PdfDocument::empty(name),
-            dpi,
+            image_style,
+            post: Default::default(),
+            page_count: 0,
+            handout: None,
+            images: Default::default(),
+            graphic_states: Default::default(),
         })
     }
 
     /// save the document to something implementing Write,
     /// please don't use BufWriter here, because the Writer
     /// will be wrapped into a BufWriter by this method.
-    pub fn save<W: io::Write>(self, to: W) -> Result<(), printpdf::Error> {
-        let mut buf_writer = io::BufWriter::new(to);
-        self.inner_doc.save(&mut buf_writer)
+    pub fn save<W: io::Write>(self, to: W) -> Result<()> {
+        let mut written = vec![];
+        self.inner_doc.save(&mut io::BufWriter::new(&mut written))?;
+
+        // printpdf can't do everything, so the rest is done with lopdf
+        let mut doc = lopdf::Document::load_mem(&written)?;
+        // printpdf writes 1.3, but the layers need 1.5 and the transparency 1.4
+        doc.version = String::from("1.5");
+        self.post.apply(&mut doc)?;
+        self.metadata.apply(&mut doc)?;
+        if self.reproducible {
+            make_reproducible(&mut doc)?;
+        }
+
+        doc.save_to(&mut io::BufWriter::new(to))?;
+        Ok(())
     }
 
     /// add a new page to the document, all future operation will be done
     /// on that new page
     pub fn new_page<S: Into<String>>(&'_ mut self, name: S) -> Page<'_> {
+        if self.handout.is_some() {
+            return self.new_handout_page(name);
+        }
+
         let (page, layer) = self.inner_doc.add_page(self.size.0, self.size.1, name);
         let page = self.inner_doc.get_page(page);
         let layer = page.get_layer(layer);
 
+        let index = self.page_count;
+        self.page_count += 1;
+        self.post.add_page();
+
         let page = Page {
             doc: self,
             page,
             layer,
+            index,
+            transform: None,
         };
 
         #[cfg(debug_assertions)]
@@ -145,6 +277,103 @@ impl Document {
         page
     }
 
+    /// makes the fonts inside the directory usable besides the system fonts
+    pub fn add_font_dir<P: AsRef<Path>>(&mut self, dir: P) {
+        self.font_config.load_fonts_dir(dir);
+    }
+
+    /// draws all following slides scaled down onto a4 pages for printing
+    pub fn use_handout(&mut self, handout: config::Handout) {
+        let size = (self.size.0.into(), self.size.1.into());
+        self.handout = Some(handout::Handout::new(handout, size));
+    }
+
+    /// gets the next place for a slide on a handout page, the page is drawn
+    /// like a normal page, but it's transformed to its place on the handout
+    fn new_handout_page<S: Into<String>>(&'_ mut self, name: S) -> Page<'_> {
+        let handout = match &mut self.handout {
+            Some(handout) => handout,
+            None => unreachable!("only called for handouts"),
+        };
+
+        if handout.starts_page() {
+            let (page, _) =
+                self.inner_doc
+                    .add_page(handout::PAGE_SIZE.0, handout::PAGE_SIZE.1, name);
+            handout.page = Some(page);
+            self.page_count += 1;
+            self.post.add_page();
+        }
+
+        let page = handout.page.expect("a handout page was added before");
+        let page = self.inner_doc.get_page(page);
+        let layer = page.add_layer("handout");
+
+        // the frame around the slide and the lines for notes
+        let slot = handout.next_slot();
+        layer.set_outline_color(Page::HANDOUT_COLOR.into());
+        layer.set_outline_thickness(0.5);
+        layer.add_shape(printpdf::Line {
+            points: slot.area.to_points(),
+            is_closed: true,
+            has_fill: false,
+            has_stroke: true,
+            is_clipping_path: false,
+        });
+        for &(start, end) in slot.notes.iter() {
+            layer.add_shape(printpdf::Line {
+                points: vec![(start, false), (end, false)],
+                is_closed: false,
+                has_fill: false,
+                has_stroke: true,
+                is_clipping_path: false,
+            });
+        }
+
+        let transform = slot.transform;
+        let operations = transform.operations(&slot.area);
+        let index = self.page_count - 1;
+
+        let mut page = Page {
+            doc: self,
+            page,
+            layer,
+            index,
+            transform: None,
+        };
+        page.add_raw(operations);
+        page.transform = Some(transform);
+
+        #[cfg(debug_assertions)]
+        page.draw_rect(&page.doc.drawing_area, Some(Page::DBG_COLOR), None);
+
+        page
+    }
+
+    /// sets an entry (title, author, subject, keywords, language or date)
+    /// of the document metadata
+    pub fn set_metadata(&mut self, key: &str, value: &str) -> Result<()> {
+        self.metadata.set(key, value)
+    }
+
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// starts a section in the outline, it contains all pages after it
+    /// until the next section starts
+    pub fn start_section(&mut self, title: &str) {
+        self.post.add_section(self.next_page_index(), title);
+    }
+
+    /// the index of the page the next slide is drawn onto
+    fn next_page_index(&self) -> usize {
+        match &self.handout {
+            Some(handout) if !handout.starts_page() => self.page_count - 1,
+            _ => self.page_count,
+        }
+    }
+
     /// gets an PdfRectangle inside the drawing bounds with an "scalor"
     /// rectangle
     pub fn scale_pdf_rect(&self, area: config::Rectangle<f64>) -> PdfRect {
@@ -237,6 +466,32 @@ impl Document {
     }
 }
 
+/// removes the unused objects, numbers the remaining ones in order
+/// and replaces the random file identifier with a hash of the document,
+/// so the same input always results in the same bytes
+fn make_reproducible(doc: &mut lopdf::Document) -> Result<()> {
+    use siphasher::sip128::{Hasher128, SipHasher};
+    use std::hash::Hasher;
+
+    doc.trailer.remove(b"ID");
+    doc.prune_objects();
+    doc.renumber_objects();
+
+    let mut written = vec![];
+    doc.save_to(&mut written)?;
+    let mut hasher = SipHasher::new();
+    hasher.write(&written);
+    let id = lopdf::Object::String(
+        hasher.finish128().as_bytes().to_vec(),
+        lopdf::StringFormat::Hexadecimal,
+    );
+
+    // the document didn't change since its creation,
+    // so both identifiers are the same
+    doc.trailer.set("ID", vec![id.clone(), id]);
+    Ok(())
+}
+
 /// a single page inside the pdf document
 /// used to drawing rectangles/text to this
 /// page
@@ -244,6 +499,20 @@ pub struct Page<'a> {
     pub doc: &'a mut Document,
     page: printpdf::PdfPageReference,
     layer: printpdf::PdfLayerReference,
+    /// the index of the page inside the document
+    index: usize,
+    /// maps the page to its place on a handout page
+    transform: Option<handout::Transform>,
+}
+
+/// restores the graphics state from before the transformation,
+/// so the next slide on the handout page isn't affected by it
+impl Drop for Page<'_> {
+    fn drop(&mut self) {
+        if self.transform.is_some() {
+            self.add_raw(vec![lopdf::content::Operation::new("Q", vec![])]);
+        }
+    }
 }
 
 impl<'a> Page<'a> {
@@ -257,25 +526,165 @@ impl<'a> Page<'a> {
         r: 1.0,
         g: 0.0,
         b: 1.0,
+        a: 1.0,
     };
 
-    pub fn draw_image<P: AsRef<std::path::Path>>(&self, path: P, area: &PdfRect) -> Result<()> {
-        let pos = area.0.orig.map(|pt| Some(Mm::from(pt)));
-        let size = area
-            .0
-            .size
-            .map(|pt| util::pt_to_px(pt.0, self.doc.dpi) as u32);
+    /// the color of the frames and note lines of a handout
+    const HANDOUT_COLOR: config::Color = config::Color {
+        r: 0.6,
+        g: 0.6,
+        b: 0.6,
+        a: 1.0,
+    };
+
+    /// adds the page to the outline of the document
+    pub fn add_bookmark(&mut self, title: &str) {
+        self.doc.post.add_bookmark(self.index, title);
+    }
+
+    /// labels the page, so links can point to it
+    pub fn add_label(&mut self, label: &str) {
+        self.doc.post.add_label(self.index, label);
+    }
+
+    /// adds operations which can't be done with printpdf,
+    /// all future operation will be done on a new layer after them
+    fn add_raw(&mut self, ops: Vec<lopdf::content::Operation>) {
+        let name = self.doc.post.add_raw(ops);
+        self.page.add_layer(name);
+        self.new_layer(post::CONTINUED_LAYER);
+    }
+
+    /// draws the image into the area. Svg images are drawn as vectors,
+    /// every other image is scaled by the fit. If an alternative text
+    /// is given, the image is tagged with it for screen readers
+    pub fn draw_image<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        area: &PdfRect,
+        fit: Fit,
+        alt: Option<&str>,
+    ) -> Result<()> {
+        let path = path.as_ref();
+        let is_svg = path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("svg"));
+
+        // marks the image as figure
+        if let Some(alt) = alt {
+            use lopdf::{content::Operation, Object};
+            let mcid = self.doc.post.add_figure(self.index, alt);
+            self.add_raw(vec![Operation::new(
+                "BDC",
+                vec![
+                    Object::Name(b"Figure".to_vec()),
+                    lopdf::dictionary! { "MCID" => mcid }.into(),
+                ],
+            )]);
+        }
 
-        let image = image::io::Reader::open(path)?.decode()?.resize_to_fill(
-            size.x,
-            size.y,
-            image::imageops::FilterType::Triangle,
+        if is_svg {
+            self.draw_svg(path, area)?;
+        } else {
+            self.draw_raster(path, area, fit)?;
+        }
+
+        if alt.is_some() {
+            self.add_raw(vec![lopdf::content::Operation::new("EMC", vec![])]);
+        }
+        Ok(())
+    }
+
+    /// draws a raster image scaled by the fit, the parts outside of the area
+    /// are clipped. Images are scaled down to the maximum dpi
+    /// of the image style and only embedded once for every size
+    fn draw_raster(&mut self, path: &Path, area: &PdfRect, fit: Fit) -> Result<()> {
+        use lopdf::{content::Operation, Object};
+
+        let data = std::fs::read(path)?;
+        let reader = image::io::Reader::new(io::Cursor::new(&data)).with_guessed_format()?;
+        let format = reader.format();
+        let (width, height) = reader.into_dimensions()?;
+
+        let config::Rectangle { orig, size } = area.0;
+        // the size of the image in pt inside the area
+        let scale = fit.scale((width, height), size);
+        let drawn = config::Point {
+            x: width as f64 * scale,
+            y: height as f64 * scale,
+        };
+
+        let style = &self.doc.image_style;
+        // images are never scaled up, the pdf viewer can do that
+        let px_scale = (util::pt_to_px(drawn.x, style.max_dpi) as f64 / width as f64).min(1.0);
+        let px_size = (
+            ((width as f64 * px_scale).round() as u32).max(1),
+            ((height as f64 * px_scale).round() as u32).max(1),
+        );
+        let key = (
+            path.canonicalize().unwrap_or_else(|_| path.to_path_buf()),
+            px_size.0,
+            px_size.1,
         );
 
-        let image = printpdf::Image::from_dynamic_image(&image);
-        let layer = self.page.add_layer("image");
+        let image = match self.doc.images.get(&key) {
+            Some(image) => image.clone(),
+            None => {
+                let raster = raster::convert(&data, format, px_size, style)?;
+
+                let image = self.doc.post.add_shared("XObject", raster.image.into());
+                if let Some(mask) = raster.mask {
+                    self.doc.post.add_indirect(&image, "SMask", mask.into());
+                }
+                self.doc.images.insert(key, image.clone());
+                image
+            }
+        };
 
-        image.add_to_layer(layer, pos.x, pos.y, None, None, None, None);
+        self.doc.post.use_shared(self.index, &image);
+        // the image is drawn into a 1x1 square, so it's scaled to its size
+        // and centered inside the clipped area
+        self.add_raw(vec![
+            Operation::new("q", vec![]),
+            Operation::new(
+                "re",
+                vec![
+                    orig.x.0.into(),
+                    orig.y.0.into(),
+                    size.x.0.into(),
+                    size.y.0.into(),
+                ],
+            ),
+            Operation::new("W", vec![]),
+            Operation::new("n", vec![]),
+            Operation::new(
+                "cm",
+                vec![
+                    drawn.x.into(),
+                    0.into(),
+                    0.into(),
+                    drawn.y.into(),
+                    (orig.x.0 - (drawn.x - size.x.0) / 2.0).into(),
+                    (orig.y.0 - (drawn.y - size.y.0) / 2.0).into(),
+                ],
+            ),
+            Operation::new("Do", vec![Object::Name(image.name.into_bytes())]),
+            Operation::new("Q", vec![]),
+        ]);
+        Ok(())
+    }
+
+    /// draws a svg image as vector graphic, text inside it is converted to paths
+    fn draw_svg(&mut self, path: &Path, area: &PdfRect) -> Result<()> {
+        let options = usvg::Options {
+            resources_dir: path.parent().map(|p| p.to_path_buf()),
+            fontdb: self.doc.font_config.clone(),
+            ..Default::default()
+        };
+        let tree = usvg::Tree::from_data(&std::fs::read(path)?, &options)?;
+
+        let ops = svg::to_operations(&tree, area, &mut self.doc.post, self.index);
+        self.add_raw(ops);
         Ok(())
     }
 
@@ -303,14 +712,157 @@ impl<'a> Page<'a> {
         layer.add_shape(line)
     }
 
+    /// fills the whole slide with the color
+    pub fn draw_background(&mut self, color: config::Color) {
+        let size = (self.doc.size.0.into(), self.doc.size.1.into());
+        let page = config::Rectangle {
+            orig: config::Point { x: 0.0, y: 0.0 },
+            size: config::Point { x: 1.0, y: 1.0 },
+        };
+
+        self.draw_shape(
+            &PdfRect::from(page, size),
+            &config::Shape::Rectangle,
+            Some(color),
+            None,
+        );
+    }
+
+    /// draws the shape into the area. If fill_color or stroke is some, the shape
+    /// is filled or gets an outline respectively, lines are never filled
+    pub fn draw_shape(
+        &mut self,
+        area: &PdfRect,
+        shape: &config::Shape,
+        fill_color: Option<config::Color>,
+        stroke: Option<&Stroke<'_>>,
+    ) {
+        let (points, is_closed) = shape::points(area, shape);
+        let fill_color = fill_color.filter(|_| is_closed);
+        let translucent = self.begin_opacity(fill_color, stroke.map(|s| s.color));
+        let layer = &self.layer;
+
+        if let Some(stroke) = stroke {
+            let dash = |i: usize| stroke.dash.get(i).copied();
+            layer.set_outline_thickness(stroke.width);
+            layer.set_line_dash_pattern(printpdf::LineDashPattern::new(
+                0,
+                dash(0),
+                dash(1),
+                dash(2),
+                dash(3),
+                dash(4),
+                dash(5),
+            ));
+        }
+
+        self.set_color(stroke.map(|s| s.color), fill_color);
+        layer.add_shape(printpdf::Line {
+            points,
+            is_closed,
+            has_fill: fill_color.is_some(),
+            has_stroke: stroke.is_some(),
+            is_clipping_path: false,
+        });
+
+        // back to the defaults, so following outlines aren't affected
+        if stroke.is_some() {
+            layer.set_outline_thickness(1.0);
+            layer.set_line_dash_pattern(printpdf::LineDashPattern::default());
+        }
+        self.end_opacity(translucent);
+    }
+
+    /// fills the shape with the gradient, lines can't be filled
+    pub fn draw_gradient(&mut self, area: &PdfRect, shape: &config::Shape, gradient: &Gradient) {
+        use lopdf::{content::Operation, dictionary, Object};
+
+        let (points, is_closed) = shape::points(area, shape);
+        if !is_closed {
+            return;
+        }
+
+        let config::Rectangle { orig, size } = area.0;
+        let (w, h) = (size.x.0, size.y.0);
+        let (cx, cy) = (orig.x.0 + w / 2.0, orig.y.0 + h / 2.0);
+        let (shading_type, coords, from, to) = match *gradient {
+            Gradient::Linear { from, to, angle } => {
+                let (dx, dy) = (angle.to_radians().cos(), angle.to_radians().sin());
+                // long enough to reach every corner of the area
+                let half = (w * dx.abs() + h * dy.abs()) / 2.0;
+                let coords = [
+                    cx - dx * half,
+                    cy - dy * half,
+                    cx + dx * half,
+                    cy + dy * half,
+                ];
+                (2, coords.to_vec(), from, to)
+            }
+            Gradient::Radial { from, to } => {
+                let radius = w.max(h) / 2.0;
+                (3, vec![cx, cy, 0.0, cx, cy, radius], from, to)
+            }
+        };
+
+        let rgb = |c: config::Color| vec![c.r.into(), c.g.into(), c.b.into()];
+        let shading = dictionary! {
+            "ShadingType" => shading_type,
+            "ColorSpace" => "DeviceRGB",
+            "Coords" => coords.into_iter().map(Object::Real).collect::<Vec<_>>(),
+            "Function" => dictionary! {
+                "FunctionType" => 2,
+                "Domain" => vec![0.into(), 1.into()],
+                "C0" => rgb(from),
+                "C1" => rgb(to),
+                "N" => 1,
+            },
+            // the colors continue beyond the coords
+            "Extend" => vec![true.into(), true.into()],
+        };
+        let name = self
+            .doc
+            .post
+            .add_resource(self.index, "Shading", shading.into());
+
+        let translucent = self.begin_opacity(Some(from), None);
+        // clip to the shape and paint the shading inside of it
+        let mut ops = vec![Operation::new("q", vec![])];
+        ops.extend(
+            printpdf::Line {
+                points,
+                is_closed,
+                has_fill: false,
+                has_stroke: false,
+                is_clipping_path: true,
+            }
+            .into_stream_op(),
+        );
+        ops.push(Operation::new("sh", vec![Object::Name(name.into_bytes())]));
+        ops.push(Operation::new("Q", vec![]));
+        self.add_raw(ops);
+        self.end_opacity(translucent);
+    }
+
     /// draw the text with the text args.
     /// if the text exceeds the horizontal boundaries, it will be word wrapped
     pub fn draw_text(&mut self, args: &TextArgs<'_>, text: &str) -> Result<Pt> {
+        self.draw_linked_text(args, text, &[])
+    }
+
+    /// draws the text like draw_text, the links are drawn in the link style
+    /// and get clickable areas over them
+    pub fn draw_linked_text(
+        &mut self,
+        args: &TextArgs<'_>,
+        text: &str,
+        links: &[Link<'_>],
+    ) -> Result<Pt> {
         // draw the box outlines in debug mode
         #[cfg(debug_assertions)]
         self.draw_rect(&args.area, Some(Self::DBG_COLOR), None);
 
         // set the colors
+        let translucent = self.begin_opacity(args.foreground, None);
         self.set_color(None, args.foreground);
 
         // get the fonts
@@ -331,14 +883,75 @@ impl<'a> Page<'a> {
 
         let mut i = 0;
         let mut start = 0; // start at index 0, duh
+                           // the clickable areas of the links
+        let mut link_areas = vec![];
 
         for line in beginnings.iter() {
             // prepare line
             let end = line.end_index;
             let pos = pos_args.get_position(i);
 
+            // the x position of the text at the index inside the line
+            let x_at = |index: usize| {
+                let width = rt_font.text_width(font_size as f32, text[start..index].chars());
+                Pt::from(pos.x) + Pt(width.sum::<f32>() as f64)
+            };
+            let mut drawn = start;
+
+            let line_links = links
+                .iter()
+                .filter(|l| l.range.start < end && l.range.end > start);
+
+            for link in line_links {
+                let range = link.range.start.max(start)..link.range.end.min(end);
+                let x = x_at(range.start);
+                let link_width = x_at(range.end) - x;
+
+                // the text before the link
+                if drawn < range.start {
+                    let before = &text[drawn..range.start];
+                    layer.use_text(before, font_size, x_at(drawn).into(), pos.y, pdf_font);
+                }
+
+                let color = args.link.color.or(args.foreground);
+                self.set_color(None, color);
+                layer.use_text(&text[range.clone()], font_size, x.into(), pos.y, pdf_font);
+
+                if args.link.underline {
+                    let underline = PdfRect(config::Rectangle {
+                        orig: config::Point {
+                            x,
+                            y: Pt::from(pos.y) - Pt(font_size * 0.1),
+                        },
+                        size: config::Point {
+                            x: link_width,
+                            y: Pt(font_size * 0.05),
+                        },
+                    });
+                    self.draw_rect(&underline, None, color);
+                }
+                self.set_color(None, args.foreground);
+
+                // the area from the lowest to the highest point of the line
+                let area = PdfRect(config::Rectangle {
+                    orig: config::Point {
+                        x,
+                        y: Pt::from(pos.y) + rt_font.descent * font_size,
+                    },
+                    size: config::Point {
+                        x: link_width,
+                        y: Pt(pos_args.line_height),
+                    },
+                });
+                link_areas.push((area, &link.target));
+                drawn = range.end;
+            }
+
             //dbg!(&text[start..end], start, end, line.width, pos);
-            layer.use_text(&text[start..end], font_size, pos.x, pos.y, pdf_font);
+            if drawn < end {
+                let rest = &text[drawn..end];
+                layer.use_text(rest, font_size, x_at(drawn).into(), pos.y, pdf_font);
+            }
 
             // the end is always at a whitespace
             // except for the last
@@ -346,7 +959,17 @@ impl<'a> Page<'a> {
             i += 1; // increase the index
         }
 
-        Ok(Pt(i as f64) * pos_args.line_height)
+        let height = Pt(i as f64) * pos_args.line_height;
+        for (area, target) in link_areas {
+            let area = match &self.transform {
+                Some(transform) => transform.apply(&area),
+                None => area,
+            };
+            self.doc.post.add_link(self.index, &area, target);
+        }
+        self.end_opacity(translucent);
+
+        Ok(height)
     }
 
     fn set_color(&self, stroke_color: Option<config::Color>, fill_color: Option<config::Color>) {
@@ -360,6 +983,50 @@ impl<'a> Page<'a> {
         }
     }
 
+    /// makes everything drawn until end_opacity translucent, if one of the
+    /// colors isn't opaque. Returns if it did, which is passed to end_opacity
+    fn begin_opacity(
+        &mut self,
+        fill: Option<config::Color>,
+        stroke: Option<config::Color>,
+    ) -> bool {
+        use lopdf::{content::Operation, dictionary, Object};
+
+        let to_key = |c: Option<config::Color>| c.map_or(1000, |c| (c.a * 1000.0).round() as u16);
+        let key = (self.index, to_key(fill), to_key(stroke));
+        if (key.1, key.2) == (1000, 1000) {
+            return false;
+        }
+
+        let post = &mut self.doc.post;
+        let name = self
+            .doc
+            .graphic_states
+            .entry(key)
+            .or_insert_with(|| {
+                let state = dictionary! {
+                    "Type" => "ExtGState",
+                    "ca" => key.1 as f64 / 1000.0,
+                    "CA" => key.2 as f64 / 1000.0,
+                };
+                post.add_resource(key.0, "ExtGState", state.into())
+            })
+            .clone();
+
+        self.add_raw(vec![
+            Operation::new("q", vec![]),
+            Operation::new("gs", vec![Object::Name(name.into_bytes())]),
+        ]);
+        true
+    }
+
+    /// restores the opacity from before begin_opacity
+    fn end_opacity(&mut self, translucent: bool) {
+        if translucent {
+            self.add_raw(vec![lopdf::content::Operation::new("Q", vec![])]);
+        }
+    }
+
     /// splits the text into lines which are
     /// inside the horizontal boundaries
     fn get_lines<'b>(
@@ -401,7 +1068,8 @@ fn is_line_end(
         if let Some((i, w)) = o {
             p_sum += w;
 
-            if p_sum > max_width {
+            // a word wider than the line stays on it, when it's the first one
+            if p_sum > max_width && p_sum > w {
                 let line_width = p_sum - w;
                 p_sum = w;
 
@@ -472,6 +1140,42 @@ mod tests {
         equal_within_error(result_y.0, expected_y);
     }
 
+    #[test]
+    fn line_ends() {
+        // words of "ab cd ef" with a width of 2 and a whitespace of 1
+        let words = [Some((0, 2.0)), Some((3, 2.0)), Some((6, 2.0)), None];
+        let ends = |max_width| {
+            words
+                .iter()
+                .cloned()
+                .filter_map(super::is_line_end(max_width, 1.0, 8))
+                .map(|l| (l.end_index, l.width))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(ends(10.0), vec![(8, 9.0)]);
+        assert_eq!(ends(5.0), vec![(5, 6.0), (8, 2.0)]);
+        // a word wider than the line doesn't leave an empty line
+        assert_eq!(ends(1.0), vec![(2, 3.0), (5, 2.0), (8, 2.0)]);
+    }
+
+    #[test]
+    fn fit_scale() {
+        use super::Fit;
+        let area = Point {
+            x: Pt(200.0),
+            y: Pt(100.0),
+        };
+
+        // a square image is cropped at the top and bottom or fitted with space on the sides
+        assert_eq!(Fit::Cover.scale((50, 50), area), 4.0);
+        assert_eq!(Fit::Contain.scale((50, 50), area), 2.0);
+        // an image with the ratio of the area fills it either way
+        assert_eq!(Fit::Cover.scale((400, 200), area), 0.5);
+        assert_eq!(Fit::Contain.scale((400, 200), area), 0.5);
+        assert_eq!(Fit::Contain.scale((100, 400), area), 0.25);
+    }
+
     const RECT_SIZE: (Pt, Pt) = (Pt(100.0), Pt(100.0));
     #[test]
     fn rect_upperleft_origin() {
@@ -0,0 +1,981 @@
+use super::{LinkTarget, PdfRect, Result};
+use lopdf::{
+    content::Content, content::Operation, dictionary, Dictionary, Object, ObjectId, StringFormat,
+};
+use std::collections::HashMap;
+
+/// prefix of the layers slidmk only adds to insert raw operations,
+/// they're removed after the operations are inserted
+const HELPER_PREFIX: &str = "slidmk ";
+/// prefix of the layers which are only placeholders
+/// for raw operations
+const RAW_PREFIX: &str = "slidmk raw ";
+/// the layer following raw operations, its content is kept without the layer
+pub const CONTINUED_LAYER: &str = "slidmk content";
+
+/// everything printpdf can't express, it gets patched into the
+/// document after printpdf has written it
+#[derive(Default)]
+pub struct PostProcess {
+    /// operations which replace the placeholder layer
+    /// with the same name
+    raw_layers: HashMap<String, Vec<Operation>>,
+    /// the alternative texts of the tagged figures for every page,
+    /// the index inside the Vec is the marked content id
+    figures: Vec<Vec<String>>,
+    /// additional resources used by the raw operations of every page
+    resources: Vec<Vec<Resource>>,
+    /// objects which can be used by several pages, but are only written once,
+    /// with entries of them which are written as their own objects
+    shared: Vec<(Object, Vec<(&'static str, Object)>)>,
+    /// the amount of added resources, used for unique names
+    resource_count: usize,
+    /// the bookmarks of the document, after the first section
+    /// every bookmark is added to the last section
+    outline: Vec<Bookmark>,
+    sectioned: bool,
+    /// the clickable areas of every page with their targets
+    links: Vec<Vec<([f64; 4], Destination)>>,
+    /// the labels with the index of the page they belong to
+    labels: HashMap<String, usize>,
+}
+
+enum Destination {
+    Uri(String),
+    Label(String),
+}
+
+/// an entry of the document outline
+struct Bookmark {
+    title: String,
+    /// the index of the page it links to
+    page: usize,
+    children: Vec<Bookmark>,
+}
+
+/// a resource used by the operations of a page
+struct Resource {
+    /// the category inside the resource dictionary (ExtGState, XObject, ...)
+    category: &'static str,
+    name: String,
+    object: ResourceObject,
+}
+
+enum ResourceObject {
+    /// only used by this page, streams are written as their own objects
+    Direct(Object),
+    /// the index of the shared object
+    Shared(usize),
+}
+
+/// a reference to an object which can be used by several pages
+#[derive(Clone)]
+pub struct SharedResource {
+    category: &'static str,
+    index: usize,
+    /// the name to reference the resource in the operations
+    pub name: String,
+}
+
+impl PostProcess {
+    /// registers a page, must be called for every page in the same order
+    /// as they're added to the document
+    pub fn add_page(&mut self) {
+        self.figures.push(vec![]);
+        self.resources.push(vec![]);
+        self.links.push(vec![]);
+    }
+
+    /// stores the raw operations and returns the name of the layer they replace
+    pub fn add_raw(&mut self, ops: Vec<Operation>) -> String {
+        let name = format!("{}{}", RAW_PREFIX, self.raw_layers.len());
+        self.raw_layers.insert(name.clone(), ops);
+        name
+    }
+
+    /// adds a resource to the page and returns the name to reference it.
+    /// Streams are added as indirect objects
+    pub fn add_resource(&mut self, page: usize, category: &'static str, object: Object) -> String {
+        let name = self.resource_name();
+        self.resources[page].push(Resource {
+            category,
+            name: name.clone(),
+            object: ResourceObject::Direct(object),
+        });
+
+        name
+    }
+
+    /// adds an object which can be used as resource by several pages,
+    /// but is only written once
+    pub fn add_shared(&mut self, category: &'static str, object: Object) -> SharedResource {
+        self.shared.push((object, vec![]));
+        SharedResource {
+            category,
+            index: self.shared.len() - 1,
+            name: self.resource_name(),
+        }
+    }
+
+    /// adds an entry to the dictionary of the shared resource,
+    /// the object is written on its own and only referenced, ex. for streams
+    pub fn add_indirect(&mut self, to: &SharedResource, key: &'static str, object: Object) {
+        self.shared[to.index].1.push((key, object));
+    }
+
+    /// makes the shared resource usable for the page
+    pub fn use_shared(&mut self, page: usize, shared: &SharedResource) {
+        let resources = &mut self.resources[page];
+
+        if !resources.iter().any(|r| r.name == shared.name) {
+            resources.push(Resource {
+                category: shared.category,
+                name: shared.name.clone(),
+                object: ResourceObject::Shared(shared.index),
+            });
+        }
+    }
+
+    fn resource_name(&mut self) -> String {
+        self.resource_count += 1;
+        format!("SM{}", self.resource_count - 1)
+    }
+
+    /// tags a figure on the page with an alternative text
+    /// and returns the marked content id for it
+    pub fn add_figure(&mut self, page: usize, alt: &str) -> i64 {
+        let figures = &mut self.figures[page];
+        figures.push(String::from(alt));
+        figures.len() as i64 - 1
+    }
+
+    /// adds a bookmark to the page to the outline
+    pub fn add_bookmark(&mut self, page: usize, title: &str) {
+        let bookmark = Bookmark {
+            title: String::from(title),
+            page,
+            children: vec![],
+        };
+
+        match self.outline.last_mut() {
+            Some(section) if self.sectioned => section.children.push(bookmark),
+            _ => self.outline.push(bookmark),
+        }
+    }
+
+    /// starts a new section at the page, all following bookmarks are inside of it
+    pub fn add_section(&mut self, page: usize, title: &str) {
+        self.outline.push(Bookmark {
+            title: String::from(title),
+            page,
+            children: vec![],
+        });
+        self.sectioned = true;
+    }
+
+    /// labels the page, so links can point to it
+    pub fn add_label(&mut self, page: usize, label: &str) {
+        if self.labels.insert(String::from(label), page).is_some() {
+            eprintln!(
+                "warning the label {} is used for more than one slide",
+                label
+            );
+        }
+    }
+
+    /// makes the area of the page a link to the target
+    pub fn add_link(&mut self, page: usize, area: &PdfRect, target: &LinkTarget<'_>) {
+        let crate::config::Rectangle { orig, size } = area.0;
+        let rect = [orig.x.0, orig.y.0, orig.x.0 + size.x.0, orig.y.0 + size.y.0];
+        let destination = match *target {
+            LinkTarget::Uri(uri) => Destination::Uri(String::from(uri)),
+            LinkTarget::Label(label) => Destination::Label(String::from(label)),
+        };
+
+        self.links[page].push((rect, destination));
+    }
+
+    /// applies all the changes to the written document
+    pub fn apply(mut self, doc: &mut lopdf::Document) -> Result<()> {
+        let pages: Vec<_> = doc.get_pages().into_values().collect();
+        let shared: Vec<_> = self
+            .shared
+            .drain(..)
+            .map(|(mut object, indirect)| {
+                let dict = match &mut object {
+                    Object::Stream(s) => Some(&mut s.dict),
+                    Object::Dictionary(d) => Some(d),
+                    _ => None,
+                };
+                if let Some(dict) = dict {
+                    for (key, entry) in indirect {
+                        dict.set(key, doc.add_object(entry));
+                    }
+                }
+                doc.add_object(object)
+            })
+            .collect();
+
+        let mut helper_layers = vec![];
+        for (i, (&page, resources)) in pages.iter().zip(self.resources.iter()).enumerate() {
+            helper_layers.append(&mut self.replace_raw_layers(doc, page)?);
+            add_resources(doc, page, resources, &shared)?;
+            self.add_links(doc, page, &self.links[i], &pages)?;
+        }
+
+        remove_layers(doc, &helper_layers)?;
+
+        if self.figures.iter().any(|f| !f.is_empty()) {
+            self.add_structure_tree(doc, &pages)?;
+        }
+
+        if !self.outline.is_empty() && !pages.is_empty() {
+            let root = doc.new_object_id();
+            let (first, last, count) = add_bookmarks(doc, &self.outline, root, &pages);
+            doc.objects.insert(
+                root,
+                dictionary! {
+                    "Type" => "Outlines",
+                    "First" => first,
+                    "Last" => last,
+                    "Count" => count,
+                }
+                .into(),
+            );
+
+            let catalog = catalog_mut(doc)?;
+            // printpdf writes an empty outline
+            let empty = catalog.get(b"Outlines").and_then(Object::as_reference);
+            catalog.set("Outlines", root);
+            catalog.set("PageMode", "UseOutlines");
+            if let Ok(empty) = empty {
+                doc.objects.remove(&empty);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// replaces the placeholder layers of the page with their operations and
+    /// unwraps the layers following them. Returns the removed layers of the page
+    fn replace_raw_layers(
+        &self,
+        doc: &mut lopdf::Document,
+        page: ObjectId,
+    ) -> Result<Vec<ObjectId>> {
+        let layer_names = layer_names(doc, page);
+        if layer_names.is_empty() {
+            return Ok(vec![]);
+        }
+        let content = doc.get_and_decode_page_content(page)?;
+
+        let mut operations = Vec::with_capacity(content.operations.len());
+        let mut ops = content.operations.into_iter();
+        // if the marked contents which are open are kept,
+        // the helper layers are removed with their end
+        let mut kept = vec![];
+
+        while let Some(op) = ops.next() {
+            let layer = match (op.operator.as_str(), op.operands.as_slice()) {
+                ("BDC", [Object::Name(oc), Object::Name(mc)]) if oc == b"OC" => layer_names.get(mc),
+                _ => None,
+            };
+
+            match (
+                op.operator.as_str(),
+                layer.map(|name| self.raw_layers.get(name)),
+            ) {
+                (_, Some(Some(raw))) => {
+                    skip_marked_content(&mut ops);
+                    operations.extend(raw.iter().cloned());
+                }
+                (_, Some(None)) => kept.push(false),
+                ("BDC", None) | ("BMC", None) => {
+                    kept.push(true);
+                    operations.push(op);
+                }
+                ("EMC", None) => {
+                    if kept.pop().unwrap_or(true) {
+                        operations.push(op);
+                    }
+                }
+                _ => operations.push(op),
+            }
+        }
+
+        doc.change_page_content(page, Content { operations }.encode()?)?;
+
+        let resources = dict_entry_mut(doc, page, b"Resources")?;
+        let properties = dict_entry_mut(doc, resources, b"Properties")?;
+        let properties = doc.get_object_mut(properties)?.as_dict_mut()?;
+        Ok(layer_names
+            .keys()
+            .filter_map(|mc| properties.remove(mc))
+            .filter_map(|ocg| ocg.as_reference().ok())
+            .collect())
+    }
+
+    /// adds link annotations for the areas to the page
+    fn add_links(
+        &self,
+        doc: &mut lopdf::Document,
+        page: ObjectId,
+        links: &[([f64; 4], Destination)],
+        pages: &[ObjectId],
+    ) -> Result<()> {
+        let mut annotations = vec![];
+
+        for (rect, destination) in links {
+            let mut annotation = dictionary! {
+                "Type" => "Annot",
+                "Subtype" => "Link",
+                "Rect" => rect.iter().map(|&v| v.into()).collect::<Vec<Object>>(),
+                "Border" => vec![0.into(), 0.into(), 0.into()],
+            };
+
+            match self.link_target(destination, pages) {
+                Ok((key, target)) => annotation.set(key, target),
+                Err(warning) => {
+                    eprintln!("{}", warning);
+                    continue;
+                }
+            }
+
+            annotations.push(doc.add_object(annotation).into());
+        }
+
+        if annotations.is_empty() {
+            return Ok(());
+        }
+
+        let page = doc.get_object_mut(page)?.as_dict_mut()?;
+        match page.get_mut(b"Annots") {
+            Ok(Object::Array(existing)) => existing.extend(annotations),
+            _ => page.set("Annots", annotations),
+        }
+
+        Ok(())
+    }
+
+    /// the entry of the link annotation which leads to the destination,
+    /// a label without a slide gives the warning for it
+    fn link_target(
+        &self,
+        destination: &Destination,
+        pages: &[ObjectId],
+    ) -> std::result::Result<(&'static str, Object), String> {
+        match destination {
+            Destination::Uri(uri) => Ok((
+                "A",
+                dictionary! {
+                    "S" => "URI",
+                    "URI" => Object::string_literal(uri.as_str()),
+                }
+                .into(),
+            )),
+            Destination::Label(label) => match self.labels.get(label) {
+                Some(&target) => Ok(("Dest", vec![pages[target].into(), "Fit".into()].into())),
+                None => Err(format!(
+                    "warning there is no slide with the label {}",
+                    label
+                )),
+            },
+        }
+    }
+
+    /// builds the structure tree, so screen readers can find the figures
+    /// and their alternative texts
+    fn add_structure_tree(&self, doc: &mut lopdf::Document, pages: &[ObjectId]) -> Result<()> {
+        let root_id = doc.new_object_id();
+        let mut elements = vec![];
+        let mut parent_tree = vec![];
+
+        for (i, (&page, figures)) in pages.iter().zip(self.figures.iter()).enumerate() {
+            let page_elements: Vec<Object> = figures
+                .iter()
+                .enumerate()
+                .map(|(mcid, alt)| {
+                    doc.add_object(dictionary! {
+                        "Type" => "StructElem",
+                        "S" => "Figure",
+                        "P" => root_id,
+                        "Pg" => page,
+                        "K" => mcid as i64,
+                        "Alt" => text_string(alt),
+                    })
+                    .into()
+                })
+                .collect();
+
+            if let Ok(Object::Dictionary(dict)) = doc.get_object_mut(page) {
+                dict.set("StructParents", i as i64);
+            }
+
+            elements.extend(page_elements.iter().cloned());
+            parent_tree.push((i as i64).into());
+            parent_tree.push(page_elements.into());
+        }
+
+        doc.objects.insert(
+            root_id,
+            dictionary! {
+                "Type" => "StructTreeRoot",
+                "K" => elements,
+                "ParentTree" => dictionary! { "Nums" => parent_tree },
+                "ParentTreeNextKey" => pages.len() as i64,
+            }
+            .into(),
+        );
+
+        let catalog = catalog_mut(doc)?;
+        catalog.set("StructTreeRoot", root_id);
+        catalog.set("MarkInfo", dictionary! { "Marked" => true });
+
+        Ok(())
+    }
+}
+
+/// gets the mutable catalog of the document
+pub fn catalog_mut(doc: &mut lopdf::Document) -> Result<&mut Dictionary> {
+    let id = doc.trailer.get(b"Root")?.as_reference()?;
+    Ok(doc.get_object_mut(id)?.as_dict_mut()?)
+}
+
+/// encodes the text as a pdf text string,
+/// everything outside of ascii is written in UTF-16BE
+pub fn text_string(text: &str) -> Object {
+    if text.is_ascii() {
+        Object::string_literal(text)
+    } else {
+        let bytes = [0xFE, 0xFF]
+            .iter()
+            .copied()
+            .chain(text.encode_utf16().flat_map(|u| u.to_be_bytes()))
+            .collect();
+        Object::String(bytes, StringFormat::Hexadecimal)
+    }
+}
+
+/// adds the bookmarks and their children as outline items to the document and
+/// returns the first and last item together with the amount of visible items
+fn add_bookmarks(
+    doc: &mut lopdf::Document,
+    bookmarks: &[Bookmark],
+    parent: ObjectId,
+    pages: &[ObjectId],
+) -> (ObjectId, ObjectId, i64) {
+    let ids: Vec<_> = bookmarks.iter().map(|_| doc.new_object_id()).collect();
+    let mut count = bookmarks.len() as i64;
+
+    for (i, bookmark) in bookmarks.iter().enumerate() {
+        // sections at the end of the document point to the last page
+        let page = pages[bookmark.page.min(pages.len() - 1)];
+        let mut item = dictionary! {
+            "Title" => text_string(&bookmark.title),
+            "Parent" => parent,
+            "Dest" => vec![page.into(), "Fit".into()],
+        };
+
+        if i > 0 {
+            item.set("Prev", ids[i - 1]);
+        }
+        if let Some(&next) = ids.get(i + 1) {
+            item.set("Next", next);
+        }
+        if !bookmark.children.is_empty() {
+            let (first, last, children) = add_bookmarks(doc, &bookmark.children, ids[i], pages);
+            item.set("First", first);
+            item.set("Last", last);
+            // positive, so the section is open
+            item.set("Count", children);
+            count += children;
+        }
+
+        doc.objects.insert(ids[i], item.into());
+    }
+
+    (ids[0], ids[ids.len() - 1], count)
+}
+
+/// adds the resources to the ones already used by the page
+fn add_resources(
+    doc: &mut lopdf::Document,
+    page: ObjectId,
+    added: &[Resource],
+    shared: &[ObjectId],
+) -> Result<()> {
+    for resource in added {
+        let object = match &resource.object {
+            // streams can't be inside a dictionary directly
+            ResourceObject::Direct(o @ Object::Stream(_)) => doc.add_object(o.clone()).into(),
+            ResourceObject::Direct(o) => o.clone(),
+            ResourceObject::Shared(index) => shared[*index].into(),
+        };
+
+        let resources = dict_entry_mut(doc, page, b"Resources")?;
+        let category = dict_entry_mut(doc, resources, resource.category.as_bytes())?;
+        doc.get_object_mut(category)?
+            .as_dict_mut()?
+            .set(resource.name.clone(), object);
+    }
+
+    Ok(())
+}
+
+/// gets the id of the dictionary at the key of another dictionary,
+/// dictionaries which are directly inside the other get moved
+/// into their own object, missing ones get created
+fn dict_entry_mut(doc: &mut lopdf::Document, of: ObjectId, key: &[u8]) -> Result<ObjectId> {
+    let entry = doc.get_dictionary(of)?.get(key).ok().cloned();
+
+    let id = match entry {
+        Some(Object::Reference(id)) => return Ok(id),
+        Some(Object::Dictionary(dict)) => doc.add_object(dict),
+        _ => doc.add_object(Dictionary::new()),
+    };
+
+    doc.get_object_mut(of)?.as_dict_mut()?.set(key.to_vec(), id);
+    Ok(id)
+}
+
+/// maps the marked content names of the page (MC0, MC1, ...)
+/// to the names of the layers
+fn layer_names(doc: &lopdf::Document, page: ObjectId) -> HashMap<Vec<u8>, String> {
+    let deref_dict = |o| doc.dereference(o).ok()?.1.as_dict().ok();
+    let properties = doc
+        .get_dictionary(page)
+        .ok()
+        .and_then(|p| deref_dict(p.get(b"Resources").ok()?))
+        .and_then(|r| deref_dict(r.get(b"Properties").ok()?));
+
+    properties
+        .into_iter()
+        .flat_map(|p| p.iter())
+        .filter_map(|(mc, ocg)| {
+            let name = deref_dict(ocg)?.get(b"Name").ok()?.as_str().ok()?;
+            Some((mc.clone(), String::from_utf8_lossy(name).into_owned()))
+        })
+        .filter(|(_, name)| name.starts_with(HELPER_PREFIX))
+        .collect()
+}
+
+/// removes the layers from the document and from the list of its layers,
+/// so they don't show up in the viewer
+fn remove_layers(doc: &mut lopdf::Document, layers: &[ObjectId]) -> Result<()> {
+    if layers.is_empty() {
+        return Ok(());
+    }
+
+    for id in layers {
+        let usage = doc
+            .get_dictionary(*id)
+            .and_then(|ocg| ocg.get(b"Usage"))
+            .and_then(Object::as_reference);
+        if let Ok(usage) = usage {
+            doc.objects.remove(&usage);
+        }
+        doc.objects.remove(id);
+    }
+
+    let retain = |dict: &mut Dictionary, key: &[u8]| {
+        if let Ok(Object::Array(refs)) = dict.get_mut(key) {
+            refs.retain(|r| !matches!(r, Object::Reference(id) if layers.contains(id)));
+        }
+    };
+
+    let catalog = catalog_mut(doc)?;
+    if let Ok(Object::Dictionary(properties)) = catalog.get_mut(b"OCProperties") {
+        retain(properties, b"OCGs");
+        if let Ok(Object::Dictionary(config)) = properties.get_mut(b"D") {
+            retain(config, b"Order");
+            retain(config, b"ON");
+            retain(config, b"OFF");
+        }
+    }
+
+    Ok(())
+}
+
+/// skips all operations until the marked content
+/// which was just opened is closed again
+fn skip_marked_content<I: Iterator<Item = Operation>>(ops: &mut I) {
+    let mut depth = 1;
+
+    for op in ops {
+        match op.operator.as_str() {
+            "BDC" | "BMC" => depth += 1,
+            "EMC" => depth -= 1,
+            _ => (),
+        }
+
+        if depth == 0 {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Destination, LinkTarget, PdfRect, PostProcess, CONTINUED_LAYER};
+    use crate::config::{Point, Rectangle};
+    use lopdf::{content::Operation, dictionary, Dictionary, Object, ObjectId, Stream};
+    use printpdf::{Mm, PdfDocument, Pt};
+    use std::io::BufWriter;
+
+    /// writes a document with printpdf, which has a page for every list
+    /// of layer names. Each layer sets the line width to its index + 1
+    fn document(pages: &[&[&str]]) -> lopdf::Document {
+        let (doc, page, layer) = PdfDocument::new("test", Mm(100.0), Mm(100.0), pages[0][0]);
+        let mut first = Some((page, layer));
+
+        for layers in pages {
+            let (page, layer) = first
+                .take()
+                .unwrap_or_else(|| doc.add_page(Mm(100.0), Mm(100.0), layers[0]));
+            doc.get_page(page)
+                .get_layer(layer)
+                .set_outline_thickness(1.0);
+
+            for (i, name) in layers.iter().enumerate().skip(1) {
+                doc.get_page(page)
+                    .add_layer(*name)
+                    .set_outline_thickness(i as f64 + 1.0);
+            }
+        }
+
+        let mut written = vec![];
+        doc.save(&mut BufWriter::new(&mut written)).unwrap();
+        lopdf::Document::load_mem(&written).unwrap()
+    }
+
+    fn pages(doc: &lopdf::Document) -> Vec<ObjectId> {
+        doc.get_pages().into_values().collect()
+    }
+
+    fn reference(dict: &Dictionary, key: &[u8]) -> ObjectId {
+        dict.get(key).and_then(Object::as_reference).unwrap()
+    }
+
+    #[test]
+    fn raw_layers() {
+        let mut post = PostProcess::default();
+        post.add_page();
+        let raw = post.add_raw(vec![
+            Operation::new("re", vec![0.into(), 0.into(), 1.into(), 1.into()]),
+            Operation::new("f", vec![]),
+        ]);
+        let mut doc = document(&[&["content", &raw, CONTINUED_LAYER]]);
+        post.apply(&mut doc).unwrap();
+
+        let page = pages(&doc)[0];
+        let operations: Vec<_> = doc
+            .get_and_decode_page_content(page)
+            .unwrap()
+            .operations
+            .into_iter()
+            .map(|op| match op.operands.as_slice() {
+                [Object::Real(width)] if op.operator == "w" => format!("w {}", width),
+                _ => op.operator,
+            })
+            .collect();
+        // the placeholder is replaced and the content after it is kept,
+        // only the layer of the normal content is left
+        assert_eq!(
+            operations,
+            ["BDC", "q", "w 1", "Q", "EMC", "re", "f", "q", "w 3", "Q"]
+        );
+
+        let resources = doc
+            .get_dictionary(page)
+            .and_then(|p| p.get(b"Resources"))
+            .and_then(|r| doc.dereference(r))
+            .and_then(|(_, r)| r.as_dict())
+            .unwrap();
+        let properties = resources
+            .get(b"Properties")
+            .and_then(|p| doc.dereference(p))
+            .and_then(|(_, p)| p.as_dict())
+            .unwrap();
+        assert_eq!(properties.len(), 1);
+
+        let ocgs = doc
+            .catalog()
+            .and_then(|c| c.get(b"OCProperties"))
+            .and_then(Object::as_dict)
+            .and_then(|p| p.get(b"OCGs"))
+            .and_then(Object::as_array)
+            .unwrap();
+        assert_eq!(ocgs.len(), 1);
+        let names: Vec<_> = doc
+            .objects
+            .values()
+            .filter_map(|o| o.as_dict().ok()?.get(b"Name").ok()?.as_str().ok())
+            .collect();
+        assert_eq!(names, [b"content"]);
+    }
+
+    #[test]
+    fn structure_tree() {
+        let mut post = PostProcess::default();
+        post.add_page();
+        post.add_page();
+        assert_eq!(post.add_figure(1, "a cat"), 0);
+        assert_eq!(post.add_figure(1, "a dog"), 1);
+        let mut doc = document(&[&["content"], &["content"]]);
+        post.apply(&mut doc).unwrap();
+
+        let pages = pages(&doc);
+        let catalog = doc.catalog().unwrap();
+        let marked = catalog
+            .get(b"MarkInfo")
+            .and_then(Object::as_dict)
+            .and_then(|m| m.get(b"Marked"))
+            .ok();
+        assert!(matches!(marked, Some(Object::Boolean(true))));
+
+        let root_id = reference(catalog, b"StructTreeRoot");
+        let root = doc.get_dictionary(root_id).unwrap();
+        let elements = root.get(b"K").and_then(Object::as_array).unwrap();
+        assert_eq!(elements.len(), 2);
+        for (mcid, (element, alt)) in elements.iter().zip(["a cat", "a dog"]).enumerate() {
+            let element = doc.get_dictionary(element.as_reference().unwrap()).unwrap();
+            assert_eq!(
+                element.get(b"S").and_then(Object::as_name_str).ok(),
+                Some("Figure")
+            );
+            assert_eq!(reference(element, b"P"), root_id);
+            assert_eq!(reference(element, b"Pg"), pages[1]);
+            assert_eq!(
+                element.get(b"K").and_then(Object::as_i64).ok(),
+                Some(mcid as i64)
+            );
+            assert_eq!(
+                element.get(b"Alt").and_then(Object::as_str).ok(),
+                Some(alt.as_bytes())
+            );
+        }
+
+        // every page has its figures in the parent tree at its index
+        let nums = root
+            .get(b"ParentTree")
+            .and_then(Object::as_dict)
+            .and_then(|t| t.get(b"Nums"))
+            .and_then(Object::as_array)
+            .unwrap();
+        assert_eq!(nums.len(), 4);
+        assert_eq!(nums[0].as_i64().ok(), Some(0));
+        assert_eq!(nums[1].as_array().map(Vec::len).ok(), Some(0));
+        assert_eq!(nums[2].as_i64().ok(), Some(1));
+        let references = |objects: &Vec<Object>| {
+            let references = objects.iter().map(Object::as_reference);
+            references.collect::<Result<Vec<_>, _>>().unwrap()
+        };
+        assert_eq!(
+            nums[3].as_array().map(references).ok(),
+            Some(references(elements))
+        );
+        for (i, &page) in pages.iter().enumerate() {
+            let parents = doc
+                .get_dictionary(page)
+                .and_then(|p| p.get(b"StructParents"))
+                .and_then(Object::as_i64)
+                .ok();
+            assert_eq!(parents, Some(i as i64));
+        }
+    }
+
+    /// the resource of the page with the name inside the category
+    fn resource<'a>(
+        doc: &'a lopdf::Document,
+        page: ObjectId,
+        category: &[u8],
+        name: &[u8],
+    ) -> &'a Object {
+        let deref_dict = |o| doc.dereference(o).and_then(|(_, o)| o.as_dict());
+        doc.get_dictionary(page)
+            .and_then(|p| deref_dict(p.get(b"Resources")?))
+            .and_then(|r| deref_dict(r.get(category)?))
+            .and_then(|c| c.get(name))
+            .unwrap()
+    }
+
+    #[test]
+    fn shared_resources() {
+        let mut post = PostProcess::default();
+        post.add_page();
+        post.add_page();
+        let image = Stream::new(dictionary! { "Subtype" => "Image" }, vec![0; 16]);
+        let shared = post.add_shared("XObject", image.into());
+        let mask = Stream::new(dictionary! { "Subtype" => "Image" }, vec![255; 16]);
+        post.add_indirect(&shared, "SMask", mask.into());
+        post.use_shared(0, &shared);
+        post.use_shared(0, &shared);
+        post.use_shared(1, &shared);
+        let state = post.add_resource(1, "ExtGState", dictionary! { "CA" => 0.5 }.into());
+        assert_ne!(state, shared.name);
+
+        let mut doc = document(&[&["content"], &["content"]]);
+        post.apply(&mut doc).unwrap();
+
+        // the image and its mask are only written once
+        let images = doc
+            .objects
+            .values()
+            .filter_map(|o| o.as_stream().ok())
+            .filter(|s| s.dict.get(b"Subtype").and_then(Object::as_name_str).ok() == Some("Image"))
+            .count();
+        assert_eq!(images, 2);
+
+        let pages = pages(&doc);
+        let name = shared.name.as_bytes();
+        let image = resource(&doc, pages[0], b"XObject", name)
+            .as_reference()
+            .unwrap();
+        assert_eq!(
+            resource(&doc, pages[1], b"XObject", name)
+                .as_reference()
+                .unwrap(),
+            image
+        );
+        let image = doc.get_object(image).and_then(Object::as_stream).unwrap();
+        let mask = reference(&image.dict, b"SMask");
+        let mask = doc.get_object(mask).and_then(Object::as_stream).unwrap();
+        assert_eq!(mask.content, vec![255; 16]);
+
+        let state = resource(&doc, pages[1], b"ExtGState", state.as_bytes());
+        assert!(state.as_dict().is_ok());
+    }
+
+    #[test]
+    fn outline() {
+        let mut post = PostProcess::default();
+        for _ in 0..4 {
+            post.add_page();
+        }
+        post.add_bookmark(0, "Title");
+        post.add_section(1, "Intro");
+        post.add_bookmark(1, "First");
+        post.add_bookmark(2, "Second");
+        // a section after the last slide points to the last page
+        post.add_section(4, "End");
+        let mut doc = document(&[&["content"], &["content"], &["content"], &["content"]]);
+        post.apply(&mut doc).unwrap();
+
+        let pages = pages(&doc);
+        let catalog = doc.catalog().unwrap();
+        assert_eq!(
+            catalog.get(b"PageMode").and_then(Object::as_name_str).ok(),
+            Some("UseOutlines")
+        );
+        let root_id = reference(catalog, b"Outlines");
+        let outlines = doc
+            .objects
+            .values()
+            .filter_map(|o| o.as_dict().ok()?.get(b"Type").ok()?.as_name_str().ok())
+            .filter(|&t| t == "Outlines")
+            .count();
+        assert_eq!(outlines, 1);
+
+        let dict = |id| doc.get_dictionary(id).unwrap();
+        let title = |id| dict(id).get(b"Title").and_then(Object::as_str).unwrap();
+        let count = |id| dict(id).get(b"Count").and_then(Object::as_i64).ok();
+        let page = |id| {
+            let dest = dict(id).get(b"Dest").and_then(Object::as_array).unwrap();
+            pages
+                .iter()
+                .position(|&p| dest[0].as_reference().ok() == Some(p))
+        };
+
+        let root = dict(root_id);
+        // the three top level items and the two inside the open section
+        assert_eq!(count(root_id), Some(5));
+        let title_id = reference(root, b"First");
+        let end = reference(root, b"Last");
+        let intro = reference(dict(title_id), b"Next");
+        assert_eq!(reference(dict(intro), b"Next"), end);
+        assert_eq!(reference(dict(end), b"Prev"), intro);
+        assert!(dict(title_id).get(b"Prev").is_err());
+        assert!(dict(end).get(b"Next").is_err());
+        for (id, text, index) in [(title_id, "Title", 0), (intro, "Intro", 1), (end, "End", 3)] {
+            assert_eq!(title(id), text.as_bytes());
+            assert_eq!(reference(dict(id), b"Parent"), root_id);
+            assert_eq!(page(id), Some(index));
+        }
+        assert_eq!(count(title_id), None);
+        assert_eq!(count(end), None);
+
+        assert_eq!(count(intro), Some(2));
+        let first = reference(dict(intro), b"First");
+        let second = reference(dict(intro), b"Last");
+        assert_eq!(reference(dict(first), b"Next"), second);
+        for (id, text, index) in [(first, "First", 1), (second, "Second", 2)] {
+            assert_eq!(title(id), text.as_bytes());
+            assert_eq!(reference(dict(id), b"Parent"), intro);
+            assert_eq!(page(id), Some(index));
+        }
+    }
+
+    #[test]
+    fn links() {
+        let mut post = PostProcess::default();
+        post.add_page();
+        post.add_page();
+        post.add_label(1, "end");
+        let area = PdfRect(Rectangle {
+            orig: Point {
+                x: Pt(10.0),
+                y: Pt(20.0),
+            },
+            size: Point {
+                x: Pt(30.0),
+                y: Pt(40.0),
+            },
+        });
+        post.add_link(0, &area, &LinkTarget::Label("end"));
+        post.add_link(0, &area, &LinkTarget::Label("missing"));
+        post.add_link(1, &area, &LinkTarget::Uri("https://example.com"));
+
+        let missing = Destination::Label(String::from("missing"));
+        assert_eq!(
+            post.link_target(&missing, &[]).err().as_deref(),
+            Some("warning there is no slide with the label missing")
+        );
+
+        let mut doc = document(&[&["content"], &["content"]]);
+        post.apply(&mut doc).unwrap();
+        let pages = pages(&doc);
+        let annotations = |page| {
+            let annots = doc.get_dictionary(page).and_then(|p| p.get(b"Annots"));
+            annots
+                .and_then(Object::as_array)
+                .unwrap()
+                .iter()
+                .map(|a| doc.get_dictionary(a.as_reference().unwrap()).unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        // the link to the missing label is left out
+        let internal = annotations(pages[0]);
+        assert_eq!(internal.len(), 1);
+        let rect: Vec<_> = internal[0]
+            .get(b"Rect")
+            .and_then(Object::as_array)
+            .unwrap()
+            .iter()
+            .map(|v| v.as_f64().unwrap())
+            .collect();
+        assert_eq!(rect, [10.0, 20.0, 40.0, 60.0]);
+        let dest = internal[0].get(b"Dest").and_then(Object::as_array).unwrap();
+        assert_eq!(dest[0].as_reference().ok(), Some(pages[1]));
+        assert_eq!(dest[1].as_name_str().ok(), Some("Fit"));
+
+        let external = annotations(pages[1]);
+        assert_eq!(external.len(), 1);
+        let uri = external[0]
+            .get(b"A")
+            .and_then(Object::as_dict)
+            .and_then(|a| a.get(b"URI"))
+            .and_then(Object::as_str);
+        assert_eq!(uri.ok(), Some(&b"https://example.com"[..]));
+    }
+}
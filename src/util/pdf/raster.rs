@@ -0,0 +1,224 @@
+use super::Result;
+use crate::config::{ImageCompression, ImageStyle};
+use lopdf::{dictionary, Stream};
+use printpdf::image::{self, codecs::jpeg::JpegEncoder, ColorType, GenericImageView, ImageFormat};
+
+/// a raster image converted into pdf image objects
+pub struct Raster {
+    pub image: Stream,
+    /// the transparency of the image, if it has one
+    pub mask: Option<Stream>,
+}
+
+/// converts the image data into a pdf image with the given size in pixels.
+/// Jpeg images which don't need to be scaled down are embedded as they are
+pub fn convert(
+    data: &[u8],
+    format: Option<ImageFormat>,
+    size: (u32, u32),
+    style: &ImageStyle,
+) -> Result<Raster> {
+    if format == Some(ImageFormat::Jpeg) {
+        if let Some(raster) = pass_through_jpeg(data, size) {
+            return Ok(raster);
+        }
+    }
+
+    let image = match format {
+        Some(format) => image::load_from_memory_with_format(data, format)?,
+        None => image::load_from_memory(data)?,
+    };
+    let image = if image.width() != size.0 || image.height() != size.1 {
+        image.resize_exact(size.0, size.1, image::imageops::FilterType::Triangle)
+    } else {
+        image
+    };
+
+    let mask = if image.color().has_alpha() {
+        let alpha = image
+            .to_luma_alpha8()
+            .pixels()
+            .map(|p| p.0[1])
+            .collect::<Vec<_>>();
+        Some(flate_stream(size, "DeviceGray", alpha)?)
+    } else {
+        None
+    };
+
+    let is_gray = matches!(
+        image.color(),
+        ColorType::L8 | ColorType::La8 | ColorType::L16 | ColorType::La16
+    );
+    let (pixels, color_type, color_space) = if is_gray {
+        (image.to_luma8().into_raw(), ColorType::L8, "DeviceGray")
+    } else {
+        (image.to_rgb8().into_raw(), ColorType::Rgb8, "DeviceRGB")
+    };
+
+    let image = match style.compression {
+        ImageCompression::Flate => flate_stream(size, color_space, pixels)?,
+        ImageCompression::Jpeg => {
+            let mut encoded = vec![];
+            JpegEncoder::new_with_quality(&mut encoded, style.quality.clamp(1, 100))
+                .encode(&pixels, size.0, size.1, color_type)?;
+            image_stream(size, color_space, "DCTDecode", encoded)
+        }
+    };
+
+    Ok(Raster { image, mask })
+}
+
+/// embeds the jpeg without decoding it, if it already has the right size
+/// and a color space the pdf can use directly
+fn pass_through_jpeg(data: &[u8], size: (u32, u32)) -> Option<Raster> {
+    let (width, height, components) = jpeg_header(data)?;
+    let color_space = match components {
+        1 => "DeviceGray",
+        3 => "DeviceRGB",
+        // cmyk jpegs are often stored inverted, so they're converted instead
+        _ => return None,
+    };
+
+    if (width, height) != size {
+        return None;
+    }
+
+    Some(Raster {
+        image: image_stream(size, color_space, "DCTDecode", data.to_vec()),
+        mask: None,
+    })
+}
+
+/// reads the width, height and the amount of color components
+/// from the frame header of a jpeg with 8 bits per component
+fn jpeg_header(data: &[u8]) -> Option<(u32, u32, u8)> {
+    let u16_at = |i: usize| Some(u16::from_be_bytes([*data.get(i)?, *data.get(i + 1)?]));
+    // skip the start of image marker
+    let mut i = 2;
+
+    loop {
+        if *data.get(i)? != 0xFF {
+            return None;
+        }
+        let marker = *data.get(i + 1)?;
+
+        match marker {
+            // fill bytes before a marker
+            0xFF => i += 1,
+            // start of frame, but not the huffman/arithmetic table markers
+            0xC0..=0xCF if !matches!(marker, 0xC4 | 0xC8 | 0xCC) => {
+                if *data.get(i + 4)? != 8 {
+                    return None;
+                }
+                let height = u16_at(i + 5)? as u32;
+                let width = u16_at(i + 7)? as u32;
+                return Some((width, height, *data.get(i + 9)?));
+            }
+            _ => i += 2 + u16_at(i + 2)? as usize,
+        }
+    }
+}
+
+fn flate_stream(size: (u32, u32), color_space: &str, pixels: Vec<u8>) -> Result<Stream> {
+    let mut stream = image_stream(size, color_space, "", pixels);
+    stream.compress()?;
+    Ok(stream)
+}
+
+/// creates an image stream with 8 bits per component,
+/// an empty filter means the data isn't encoded
+fn image_stream(size: (u32, u32), color_space: &str, filter: &str, data: Vec<u8>) -> Stream {
+    let mut dict = dictionary! {
+        "Type" => "XObject",
+        "Subtype" => "Image",
+        "Width" => size.0 as i64,
+        "Height" => size.1 as i64,
+        "ColorSpace" => color_space,
+        "BitsPerComponent" => 8,
+    };
+    if !filter.is_empty() {
+        dict.set("Filter", filter);
+    }
+
+    // the data is already compressed
+    Stream::new(dict, data).with_compression(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{jpeg_header, pass_through_jpeg};
+
+    /// the markers of a jpeg up to its frame header, the image data isn't needed
+    fn jpeg(frame: u8, precision: u8, components: u8, adobe: bool) -> Vec<u8> {
+        let mut data = vec![0xFF, 0xD8];
+        // jfif header
+        data.extend([
+            0xFF, 0xE0, 0, 16, b'J', b'F', b'I', b'F', 0, 1, 1, 0, 0, 1, 0, 1, 0, 0,
+        ]);
+        if adobe {
+            data.extend([
+                0xFF, 0xEE, 0, 14, b'A', b'd', b'o', b'b', b'e', 0, 100, 0, 0, 0, 0, 2,
+            ]);
+        }
+        // a huffman table uses a marker inside the range of the frames
+        data.extend([0xFF, 0xC4, 0, 3, 0]);
+        // fill bytes are allowed before every marker
+        data.extend([0xFF, 0xFF, frame, 0, 8 + 3 * components, precision]);
+        // 300 pixels high and 400 wide
+        data.extend([0x01, 0x2C, 0x01, 0x90, components]);
+        for c in 0..components {
+            data.extend([c + 1, 0x11, 0]);
+        }
+        data.extend([0xFF, 0xDA, 0, 8]);
+        data
+    }
+
+    #[test]
+    fn frame_headers() {
+        // baseline and progressive
+        assert_eq!(jpeg_header(&jpeg(0xC0, 8, 3, false)), Some((400, 300, 3)));
+        assert_eq!(jpeg_header(&jpeg(0xC2, 8, 1, false)), Some((400, 300, 1)));
+        assert_eq!(jpeg_header(&jpeg(0xC2, 8, 4, true)), Some((400, 300, 4)));
+        // only 8 bits per component can be used directly
+        assert_eq!(jpeg_header(&jpeg(0xC1, 12, 3, false)), None);
+    }
+
+    #[test]
+    fn truncated_or_invalid() {
+        let data = jpeg(0xC0, 8, 3, false);
+        // the frame header ends with the components
+        let end = data.len() - 4 - 3 * 3;
+        for len in 0..end {
+            assert_eq!(jpeg_header(&data[..len]), None, "{} bytes", len);
+        }
+        assert_eq!(jpeg_header(&[0xFF, 0xD8, 0x00, 0xC0]), None);
+        // a segment length pointing behind the end
+        assert_eq!(jpeg_header(&[0xFF, 0xD8, 0xFF, 0xE0, 0xFF, 0xFF]), None);
+    }
+
+    #[test]
+    fn pass_through() {
+        let rgb = jpeg(0xC0, 8, 3, false);
+        let raster = pass_through_jpeg(&rgb, (400, 300)).expect("the jpeg is used as it is");
+        assert_eq!(raster.image.content, rgb);
+        let dict = &raster.image.dict;
+        assert_eq!(
+            dict.get(b"Filter").unwrap().as_name_str().unwrap(),
+            "DCTDecode"
+        );
+        assert_eq!(
+            dict.get(b"ColorSpace").unwrap().as_name_str().unwrap(),
+            "DeviceRGB"
+        );
+        assert!(raster.mask.is_none());
+
+        let gray = pass_through_jpeg(&jpeg(0xC2, 8, 1, false), (400, 300)).unwrap();
+        let color_space = gray.image.dict.get(b"ColorSpace").unwrap();
+        assert_eq!(color_space.as_name_str().unwrap(), "DeviceGray");
+
+        // scaled images are decoded
+        assert!(pass_through_jpeg(&rgb, (200, 150)).is_none());
+        // cmyk from adobe is often inverted, so it's decoded too
+        assert!(pass_through_jpeg(&jpeg(0xC0, 8, 4, true), (400, 300)).is_none());
+    }
+}
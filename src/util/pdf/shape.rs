@@ -0,0 +1,170 @@
+use super::PdfRect;
+use crate::config::{self, Shape};
+use printpdf::{Point, Pt};
+
+/// the distance of the control points for a quarter circle
+/// drawn as bezier curve, relative to the radius
+const KAPPA: f64 = 0.552_284_75;
+
+/// the points of the shape inside the area, points which start
+/// a bezier curve and its control points are marked with true.
+/// The second value is false for shapes which aren't closed
+pub fn points(area: &PdfRect, shape: &Shape) -> (Vec<(Point, bool)>, bool) {
+    let config::Rectangle { orig, size } = area.0;
+    let (x, y, w, h) = (orig.x.0, orig.y.0, size.x.0, size.y.0);
+    let point = |x: f64, y: f64, curve: bool| (Point { x: Pt(x), y: Pt(y) }, curve);
+
+    match shape {
+        Shape::Rectangle => (area.to_points(), true),
+        // from the top-left to the bottom-right corner
+        Shape::Line => (vec![point(x, y + h, false), point(x + w, y, false)], false),
+        // the points are relative to the area with a top-left origin
+        Shape::Polygon(points) => (
+            points
+                .iter()
+                .map(|p| point(x + p.x * w, y + (1.0 - p.y) * h, false))
+                .collect(),
+            true,
+        ),
+        Shape::Ellipse => {
+            let (rx, ry) = (w / 2.0, h / 2.0);
+            let (cx, cy) = (x + rx, y + ry);
+            let (kx, ky) = (rx * KAPPA, ry * KAPPA);
+
+            // four quarters, starting on the right going counterclockwise
+            let points = vec![
+                point(cx + rx, cy, true),
+                point(cx + rx, cy + ky, true),
+                point(cx + kx, cy + ry, true),
+                point(cx, cy + ry, true),
+                point(cx - kx, cy + ry, true),
+                point(cx - rx, cy + ky, true),
+                point(cx - rx, cy, true),
+                point(cx - rx, cy - ky, true),
+                point(cx - kx, cy - ry, true),
+                point(cx, cy - ry, true),
+                point(cx + kx, cy - ry, true),
+                point(cx + rx, cy - ky, true),
+                point(cx + rx, cy, true),
+            ];
+            (points, true)
+        }
+        Shape::RoundedRectangle(radius) => {
+            let r = (radius * w.min(h)).min(w.min(h) / 2.0);
+            let k = r * KAPPA;
+
+            // every side is a line followed by the curve of the next corner
+            let points = vec![
+                point(x + r, y, false),
+                point(x + w - r, y, true),
+                point(x + w - r + k, y, true),
+                point(x + w, y + r - k, true),
+                point(x + w, y + r, false),
+                point(x + w, y + h - r, true),
+                point(x + w, y + h - r + k, true),
+                point(x + w - r + k, y + h, true),
+                point(x + w - r, y + h, false),
+                point(x + r, y + h, true),
+                point(x + r - k, y + h, true),
+                point(x, y + h - r + k, true),
+                point(x, y + h - r, false),
+                point(x, y + r, true),
+                point(x, y + r - k, true),
+                point(x + r - k, y, true),
+                point(x + r, y, false),
+            ];
+            (points, true)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{points, PdfRect};
+    use crate::config::{self, Shape};
+    use printpdf::Pt;
+
+    /// an area at 10, 20 which is 100 wide and 50 high
+    fn area() -> PdfRect {
+        PdfRect(config::Rectangle {
+            orig: (Pt(10.0), Pt(20.0)).into(),
+            size: (Pt(100.0), Pt(50.0)).into(),
+        })
+    }
+
+    /// the operators printpdf writes for the points of the shape
+    fn operators(shape: &Shape) -> String {
+        let (points, is_closed) = points(&area(), shape);
+        let line = printpdf::Line {
+            points,
+            is_closed,
+            has_fill: false,
+            has_stroke: true,
+            is_clipping_path: false,
+        };
+        line.into_stream_op()
+            .iter()
+            .map(|op| op.operator.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn coordinates(shape: &Shape) -> Vec<(f64, f64)> {
+        points(&area(), shape)
+            .0
+            .iter()
+            .map(|(p, _)| (p.x.0, p.y.0))
+            .collect()
+    }
+
+    #[test]
+    fn ellipse_is_four_curves() {
+        assert_eq!(operators(&Shape::Ellipse), "m c c c c s");
+
+        let points = coordinates(&Shape::Ellipse);
+        // the curves end on the middle of every side and back at the start
+        assert_eq!(points[0], (110.0, 45.0));
+        assert_eq!(points[3], (60.0, 70.0));
+        assert_eq!(points[6], (10.0, 45.0));
+        assert_eq!(points[9], (60.0, 20.0));
+        assert_eq!(points[12], points[0]);
+    }
+
+    #[test]
+    fn rounded_rectangle_is_lines_and_curves() {
+        let shape = Shape::RoundedRectangle(0.2);
+        assert_eq!(operators(&shape), "m l c l c l c l c s");
+
+        // the radius is a share of the shorter side
+        let points = coordinates(&shape);
+        assert_eq!(points[0], (20.0, 20.0));
+        assert_eq!(points[1], (100.0, 20.0));
+        assert_eq!(points[4], (110.0, 30.0));
+        assert_eq!(points[16], points[0]);
+
+        // it's at most half of the shorter side
+        let pill = coordinates(&Shape::RoundedRectangle(2.0));
+        assert_eq!(pill[0], (35.0, 20.0));
+        assert_eq!(pill[4], (110.0, 45.0));
+    }
+
+    #[test]
+    fn straight_shapes() {
+        assert_eq!(operators(&Shape::Rectangle), "m l l l s");
+        // a line isn't closed
+        assert_eq!(operators(&Shape::Line), "m l S");
+        assert_eq!(coordinates(&Shape::Line), vec![(10.0, 70.0), (110.0, 20.0)]);
+
+        // the points of polygons have a top-left origin
+        let triangle = Shape::Polygon(vec![
+            config::Point { x: 0.5, y: 0.0 },
+            config::Point { x: 1.0, y: 1.0 },
+            config::Point { x: 0.0, y: 1.0 },
+        ]);
+        assert_eq!(operators(&triangle), "m l l s");
+        assert_eq!(
+            coordinates(&triangle),
+            vec![(60.0, 70.0), (110.0, 20.0), (10.0, 20.0)]
+        );
+    }
+}
@@ -0,0 +1,419 @@
+use super::{post::PostProcess, PdfRect};
+use crate::config;
+use lopdf::{content::Operation, dictionary, Object};
+use std::collections::{BTreeSet, HashMap};
+use usvg::NodeKind;
+
+/// converts the svg into pdf operations, which draw it centered
+/// into the area, scaled so it fits without distortion
+pub fn to_operations(
+    tree: &usvg::Tree,
+    area: &PdfRect,
+    post: &mut PostProcess,
+    page: usize,
+) -> Vec<Operation> {
+    let mut converter = Converter {
+        tree,
+        post,
+        page,
+        ops: vec![],
+        ts: usvg::Transform::default(),
+        opacity: 1.0,
+        graphic_states: HashMap::new(),
+        unsupported: BTreeSet::new(),
+    };
+
+    let config::Rectangle { orig, size } = area.0;
+    let view_box = tree.svg_node().view_box.rect;
+    let scale = (size.x.0 / view_box.width()).min(size.y.0 / view_box.height());
+    // the svg is centered inside the area
+    let padding = (
+        (size.x.0 - view_box.width() * scale) / 2.0,
+        (size.y.0 - view_box.height() * scale) / 2.0,
+    );
+
+    converter.op("q", vec![]);
+    converter.op(
+        "re",
+        vec![
+            orig.x.0.into(),
+            orig.y.0.into(),
+            size.x.0.into(),
+            size.y.0.into(),
+        ],
+    );
+    converter.op("W", vec![]);
+    converter.op("n", vec![]);
+    // flip the y axis, because svg starts at the top-left
+    converter.ts = usvg::Transform::new(
+        scale,
+        0.0,
+        0.0,
+        -scale,
+        orig.x.0 + padding.0 - view_box.x() * scale,
+        orig.y.0 + size.y.0 - padding.1 + view_box.y() * scale,
+    );
+    converter.children(&tree.root());
+    converter.op("Q", vec![]);
+
+    if !converter.unsupported.is_empty() {
+        eprintln!(
+            "warning the svg features {:?} are not supported and were skipped or simplified",
+            converter.unsupported
+        );
+    }
+
+    converter.ops
+}
+
+/// walks through the svg tree and collects the operations
+struct Converter<'a> {
+    tree: &'a usvg::Tree,
+    post: &'a mut PostProcess,
+    page: usize,
+    ops: Vec<Operation>,
+    /// the transformation from the current svg coordinates to the pdf ones,
+    /// it's applied to every point directly, because lopdf writes numbers
+    /// with only two decimal places, which is too imprecise for a scale
+    ts: usvg::Transform,
+    /// the opacity of the group the converter is currently in
+    opacity: f64,
+    /// already created graphic states for (fill, stroke) opacities
+    graphic_states: HashMap<(u16, u16), String>,
+    /// features of the svg which couldn't be converted
+    unsupported: BTreeSet<&'static str>,
+}
+
+impl<'a> Converter<'a> {
+    fn op(&mut self, operator: &str, operands: Vec<Object>) {
+        self.ops.push(Operation::new(operator, operands));
+    }
+
+    /// the current transformation with the other one applied after it
+    fn transformed(&self, other: &usvg::Transform) -> usvg::Transform {
+        let mut ts = self.ts;
+        ts.append(other);
+        ts
+    }
+
+    fn children(&mut self, node: &usvg::Node) {
+        for child in node.children() {
+            self.node(&child);
+        }
+    }
+
+    fn node(&mut self, node: &usvg::Node) {
+        match *node.borrow() {
+            NodeKind::Path(ref path) => self.path(path),
+            NodeKind::Group(ref group) => self.group(node, group),
+            NodeKind::Image(_) => {
+                self.unsupported.insert("embedded images");
+            }
+            // definitions are only drawn when referenced
+            _ => (),
+        }
+    }
+
+    fn group(&mut self, node: &usvg::Node, group: &usvg::Group) {
+        if group.mask.is_some() {
+            self.unsupported.insert("masks");
+        }
+        if group.filter.is_some() {
+            self.unsupported.insert("filters");
+        }
+
+        let (outer_ts, outer_opacity) = (self.ts, self.opacity);
+        self.ts = self.transformed(&group.transform);
+        // TODO: use transparency groups, overlapping children are drawn with the opacity each
+        self.opacity *= group.opacity.value();
+
+        self.op("q", vec![]);
+        if let Some(clip) = group.clip_path.as_ref() {
+            self.clip(clip);
+        }
+        self.children(node);
+        self.op("Q", vec![]);
+
+        self.ts = outer_ts;
+        self.opacity = outer_opacity;
+    }
+
+    /// clips everything after it with the clip path of the given id
+    fn clip(&mut self, id: &str) {
+        let clip_node = match self.tree.defs_by_id(id) {
+            Some(n) => n,
+            None => return,
+        };
+
+        let clip_path = match *clip_node.borrow() {
+            NodeKind::ClipPath(ref c) => c.clone(),
+            _ => return,
+        };
+
+        if clip_path.units == usvg::Units::ObjectBoundingBox || clip_path.clip_path.is_some() {
+            self.unsupported.insert("complex clip paths");
+            return;
+        }
+
+        for child in clip_node.children() {
+            if let NodeKind::Path(ref path) = *child.borrow() {
+                let mut ts = self.transformed(&clip_path.transform);
+                ts.append(&path.transform);
+
+                for segment in usvg::TransformedPath::new(&path.data, ts) {
+                    self.segment(segment);
+                }
+            }
+        }
+
+        self.op("W", vec![]);
+        self.op("n", vec![]);
+    }
+
+    fn path(&mut self, path: &usvg::Path) {
+        if path.visibility != usvg::Visibility::Visible {
+            return;
+        }
+
+        let fill = path
+            .fill
+            .as_ref()
+            .and_then(|f| Some((self.paint(&f.paint)?, f.opacity.value(), f.rule)));
+        let stroke = path
+            .stroke
+            .as_ref()
+            .and_then(|s| Some((self.paint(&s.paint)?, s)));
+
+        let ts = self.transformed(&path.transform);
+
+        self.op("q", vec![]);
+        self.opacity(
+            fill.map_or(1.0, |f| f.1),
+            stroke.map_or(1.0, |s| s.1.opacity.value()),
+        );
+
+        if let Some((color, ..)) = fill {
+            self.op("rg", color_operands(color));
+        }
+        if let Some((color, stroke)) = stroke {
+            self.op("RG", color_operands(color));
+            self.stroke_style(stroke, &ts);
+        }
+
+        for segment in usvg::TransformedPath::new(&path.data, ts) {
+            self.segment(segment);
+        }
+
+        let even_odd = matches!(fill, Some((_, _, usvg::FillRule::EvenOdd)));
+        let paint = match (fill.is_some(), stroke.is_some(), even_odd) {
+            (true, true, false) => "B",
+            (true, true, true) => "B*",
+            (true, false, false) => "f",
+            (true, false, true) => "f*",
+            (false, true, _) => "S",
+            (false, false, _) => "n",
+        };
+        self.op(paint, vec![]);
+        self.op("Q", vec![]);
+    }
+
+    fn segment(&mut self, segment: usvg::PathSegment) {
+        use usvg::PathSegment::*;
+        match segment {
+            MoveTo { x, y } => self.op("m", vec![x.into(), y.into()]),
+            LineTo { x, y } => self.op("l", vec![x.into(), y.into()]),
+            CurveTo {
+                x1,
+                y1,
+                x2,
+                y2,
+                x,
+                y,
+            } => self.op(
+                "c",
+                vec![
+                    x1.into(),
+                    y1.into(),
+                    x2.into(),
+                    y2.into(),
+                    x.into(),
+                    y.into(),
+                ],
+            ),
+            ClosePath => self.op("h", vec![]),
+        }
+    }
+
+    fn stroke_style(&mut self, stroke: &usvg::Stroke, ts: &usvg::Transform) {
+        use usvg::{LineCap, LineJoin};
+        let cap: i64 = match stroke.linecap {
+            LineCap::Butt => 0,
+            LineCap::Round => 1,
+            LineCap::Square => 2,
+        };
+        let join: i64 = match stroke.linejoin {
+            LineJoin::Miter => 0,
+            LineJoin::Round => 1,
+            LineJoin::Bevel => 2,
+        };
+
+        // the average scale, because the points are transformed but not the widths
+        let scale = (ts.a * ts.d - ts.b * ts.c).abs().sqrt();
+
+        self.op("w", vec![(stroke.width.value() * scale).into()]);
+        self.op("J", vec![cap.into()]);
+        self.op("j", vec![join.into()]);
+        self.op("M", vec![stroke.miterlimit.value().into()]);
+
+        if let Some(dashes) = stroke.dasharray.as_ref() {
+            let dashes = dashes
+                .iter()
+                .map(|&d| (d * scale).into())
+                .collect::<Vec<_>>();
+            let offset = stroke.dashoffset as f64 * scale;
+            self.op("d", vec![dashes.into(), offset.into()]);
+        }
+    }
+
+    /// sets the opacities for filling and stroking combined with
+    /// the opacity of the current group
+    fn opacity(&mut self, fill: f64, stroke: f64) {
+        let to_key = |o: f64| (o * self.opacity * 1000.0).round() as u16;
+        let key = (to_key(fill), to_key(stroke));
+
+        if key == (1000, 1000) {
+            return;
+        }
+
+        let post = &mut self.post;
+        let page = self.page;
+        let name = self
+            .graphic_states
+            .entry(key)
+            .or_insert_with(|| {
+                let state = dictionary! {
+                    "Type" => "ExtGState",
+                    "ca" => key.0 as f64 / 1000.0,
+                    "CA" => key.1 as f64 / 1000.0,
+                };
+                post.add_resource(page, "ExtGState", state.into())
+            })
+            .clone();
+
+        self.op("gs", vec![Object::Name(name.into_bytes())]);
+    }
+
+    /// gets the color of the paint, gradients are simplified to their first color
+    fn paint(&mut self, paint: &usvg::Paint) -> Option<usvg::Color> {
+        let id = match paint {
+            usvg::Paint::Color(c) => return Some(*c),
+            usvg::Paint::Link(id) => id,
+        };
+
+        let node = self.tree.defs_by_id(id)?;
+        let color = match *node.borrow() {
+            NodeKind::LinearGradient(ref g) => g.stops.first().map(|s| s.color),
+            NodeKind::RadialGradient(ref g) => g.stops.first().map(|s| s.color),
+            _ => None,
+        };
+
+        self.unsupported.insert(if color.is_some() {
+            "gradients"
+        } else {
+            "patterns"
+        });
+        color
+    }
+}
+
+fn color_operands(c: usvg::Color) -> Vec<Object> {
+    let to_f = |c: u8| (c as f64 / 255.0).into();
+    vec![to_f(c.red), to_f(c.green), to_f(c.blue)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::post::PostProcess, to_operations, PdfRect};
+    use crate::config::{Point, Rectangle};
+    use lopdf::Object;
+    use printpdf::Pt;
+
+    /// converts the svg for an area of 40x40 at the origin
+    /// and writes the operations as text
+    fn operations(svg: &str) -> Vec<String> {
+        let tree = usvg::Tree::from_str(svg, &usvg::Options::default()).unwrap();
+        let area = PdfRect(Rectangle {
+            orig: Point {
+                x: Pt(0.0),
+                y: Pt(0.0),
+            },
+            size: Point {
+                x: Pt(40.0),
+                y: Pt(40.0),
+            },
+        });
+        let mut post = PostProcess::default();
+        post.add_page();
+
+        to_operations(&tree, &area, &mut post, 0)
+            .into_iter()
+            .map(|op| {
+                let operands = op.operands.iter().map(|o| match o {
+                    Object::Integer(i) => i.to_string(),
+                    Object::Real(r) => format!("{}", (r * 1000.0).round() / 1000.0),
+                    Object::Name(name) => String::from_utf8_lossy(name).into_owned(),
+                    o => format!("{:?}", o),
+                });
+                operands
+                    .chain(std::iter::once(op.operator))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn paths() {
+        let ops = operations(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 20">
+                <rect width="10" height="10" fill="#ff0000"/>
+                <path d="M 0 20 L 10 20" fill="none" stroke="#0000ff" stroke-width="0.5"/>
+                <path d="M 0 0 L 10 0 L 10 10 Z" fill-rule="evenodd" visibility="hidden"/>
+            </svg>"##,
+        );
+
+        // clipped to the area
+        assert_eq!(ops[..4], ["q", "0 0 40 40 re", "W", "n"]);
+        // scaled by 2 to fit the height, centered horizontally and flipped
+        let rect = [
+            "q", "1 0 0 rg", "10 40 m", "30 40 l", "30 20 l", "10 20 l", "h", "f", "Q",
+        ];
+        assert_eq!(ops[4..13], rect);
+        // the stroke width is scaled too, the hidden path is skipped
+        let line = [
+            "q", "0 0 1 RG", "1 w", "0 J", "0 j", "4 M", "10 0 m", "30 0 l", "S", "Q",
+        ];
+        assert_eq!(ops[13..], [&line[..], &["Q"]].concat());
+    }
+
+    #[test]
+    fn opacity() {
+        let ops = operations(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 40 40">
+                <g opacity="0.5">
+                    <rect width="10" height="10" fill-opacity="0.5"/>
+                    <rect x="20" width="10" height="10"/>
+                    <rect y="20" width="10" height="10" fill-opacity="0.5"/>
+                </g>
+                <rect x="20" y="20" width="10" height="10" fill-rule="evenodd"/>
+            </svg>"##,
+        );
+
+        // the graphic states are reused for the same opacities
+        let states: Vec<_> = ops.iter().filter(|op| op.ends_with(" gs")).collect();
+        assert_eq!(states.len(), 3);
+        assert_eq!(states[0], states[2]);
+        assert_ne!(states[0], states[1]);
+        assert!(ops.contains(&String::from("f*")));
+    }
+}
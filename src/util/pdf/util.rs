@@ -29,9 +29,9 @@ pub fn pt_to_px(pt: f64, dpi: u16) -> usize {
     (pt * dpi as f64 / INCHES_PER_POINT) as usize
 }
 
-#[allow(dead_code)]
 /// different ways how the size of an pdf document
 /// can be described. Here it is always (x, y)
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Size {
     Mm(f64, f64),
     Px(usize, usize),
@@ -41,10 +41,10 @@ pub enum Size {
 impl Size {
     /// converts the size enum to a tuple
     /// with the size in Mm
-    pub fn to_mm(&self, dpi: u16) -> (Mm, Mm) {
+    pub fn to_mm(self, dpi: u16) -> (Mm, Mm) {
         let px_to_mm = |x| Mm::from(Px(x).into_pt(dpi as f64));
 
-        match *self {
+        match self {
             Size::Mm(x, y) => (Mm(x), Mm(y)),
             Size::Pt(x, y) => (Pt(x).into(), Pt(y).into()),
             Size::Px(x, y) => (px_to_mm(x), px_to_mm(y)),
@@ -58,6 +58,8 @@ pub struct RtFont<'a> {
     inner: rusttype::Font<'a>,
     scale: rusttype::Scale,
     line_height: Pt,
+    /// how far the glyphs go below the baseline at font size 1, it's negative
+    pub descent: Pt,
 }
 
 impl<'a> RtFont<'a> {
@@ -67,9 +69,10 @@ impl<'a> RtFont<'a> {
         let line_height = (v_metrics.ascent - v_metrics.descent/*+ v_metrics.line_gap*/)
             / font.units_per_em() as f32;
         Self {
-            inner: font,
             scale: rusttype::Scale::uniform(line_height),
             line_height: Pt(line_height as f64),
+            descent: Pt((v_metrics.descent / font.units_per_em() as f32) as f64),
+            inner: font,
         }
     }
 